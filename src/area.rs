@@ -0,0 +1,148 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{DeviceType, ProtectionState, Region, BLOCK_SIZE};
+use core::convert::TryInto;
+
+///
+/// The size of each of the manufacturer-specific and end-user areas.
+///
+pub const AREA_SIZE: usize = 0x40;
+
+///
+/// The two halves of the end-user programmable region at the end of a DDR4
+/// SPD image ([`Region::EndUser`], 0x180-0x1FF):  by convention, the first
+/// is used by the module manufacturer (e.g. for asset tags and provenance)
+/// and the second is left to the end user.  Like the rest of the region,
+/// both are per-unit.  Both areas lie entirely within write-protection
+/// block 3, so neither can be modified without also unprotecting the other.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Area {
+    Manufacturer,
+    EndUser,
+}
+
+impl Area {
+    pub fn base(self) -> usize {
+        let region = Region::EndUser.offsets(DeviceType::Ddr4).next().unwrap();
+
+        match self {
+            Area::Manufacturer => region,
+            Area::EndUser => region + AREA_SIZE,
+        }
+    }
+
+    pub fn limit(self) -> usize {
+        self.base() + AREA_SIZE - 1
+    }
+
+    ///
+    /// The write-protection block containing this area.
+    ///
+    pub fn block(self) -> u8 {
+        (self.base() / BLOCK_SIZE) as u8
+    }
+
+    ///
+    /// Returns the area within the given image, or `None` if the image does
+    /// not have the DDR4 layout or is too small to contain the area.
+    ///
+    pub fn within(self, buf: &[u8]) -> Option<&[u8; AREA_SIZE]> {
+        if !is_ddr4(buf) {
+            return None;
+        }
+
+        buf.get(self.base()..=self.limit())?.try_into().ok()
+    }
+
+    ///
+    /// Returns the area within the given image for modification, as
+    /// [`Area::within`] does, but only if the protection state (of the DDR4
+    /// device the image is destined for) shows the area's block to be
+    /// writable.
+    ///
+    pub fn within_mut<'a>(
+        self,
+        buf: &'a mut [u8],
+        protection: &ProtectionState,
+    ) -> Option<&'a mut [u8; AREA_SIZE]> {
+        if !is_ddr4(buf)
+            || protection.device_type().layout() != DeviceType::Ddr4
+            || protection.is_protected(self.block()) != Some(false)
+        {
+            return None;
+        }
+
+        buf.get_mut(self.base()..=self.limit())?.try_into().ok()
+    }
+}
+
+fn is_ddr4(buf: &[u8]) -> bool {
+    DeviceType::from_spd(buf).map(DeviceType::layout) == Some(DeviceType::Ddr4)
+}
+
+pub fn manufacturer_area(buf: &[u8]) -> Option<&[u8; AREA_SIZE]> {
+    Area::Manufacturer.within(buf)
+}
+
+pub fn manufacturer_area_mut<'a>(
+    buf: &'a mut [u8],
+    protection: &ProtectionState,
+) -> Option<&'a mut [u8; AREA_SIZE]> {
+    Area::Manufacturer.within_mut(buf, protection)
+}
+
+pub fn end_user_area(buf: &[u8]) -> Option<&[u8; AREA_SIZE]> {
+    Area::EndUser.within(buf)
+}
+
+pub fn end_user_area_mut<'a>(
+    buf: &'a mut [u8],
+    protection: &ProtectionState,
+) -> Option<&'a mut [u8; AREA_SIZE]> {
+    Area::EndUser.within_mut(buf, protection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DDR5_MAX_SIZE, MAX_SIZE};
+
+    #[test]
+    fn areas() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+
+        let mut protection = ProtectionState::new(DeviceType::Ddr4);
+        assert_eq!(manufacturer_area_mut(&mut buf, &protection), None);
+
+        protection.record_rps(3, false);
+        assert_eq!(end_user_area_mut(&mut buf, &protection), None);
+
+        protection.record_rps(3, true);
+        manufacturer_area_mut(&mut buf, &protection).unwrap()[0] = 0xaa;
+        end_user_area_mut(&mut buf, &protection).unwrap()[AREA_SIZE - 1] = 0xbb;
+
+        assert_eq!(buf[0x180], 0xaa);
+        assert_eq!(buf[0x1ff], 0xbb);
+        assert_eq!(manufacturer_area(&buf).unwrap()[0], 0xaa);
+        assert_eq!(end_user_area(&buf).unwrap()[AREA_SIZE - 1], 0xbb);
+        assert_eq!(end_user_area(&buf[..0x100]), None);
+
+        //
+        // A DDR5 image has no such areas.
+        //
+        let mut ddr5 = [0u8; DDR5_MAX_SIZE];
+        ddr5[0x002] = 0x12;
+        assert_eq!(manufacturer_area(&ddr5), None);
+        assert_eq!(manufacturer_area_mut(&mut ddr5, &protection), None);
+
+        assert_eq!(Area::Manufacturer.block(), 3);
+        assert_eq!(Area::EndUser.block(), 3);
+        assert_eq!(Area::Manufacturer.limit() + 1, Area::EndUser.base());
+    }
+}
@@ -0,0 +1,75 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::Operation;
+
+///
+/// The bus operations needed to execute a [`Plan`], addressed by 7-bit
+/// device code.  Implement this over an I2C/SMBus driver to talk to real
+/// devices, or use [`MockSpdDevice`](crate::MockSpdDevice) in tests.
+///
+pub trait SpdBus {
+    type Error;
+
+    fn write(&mut self, code: u8, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    fn read(&mut self, code: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    ///
+    /// Write then read with a repeated start.  The default implementation
+    /// issues the two as separate transactions.
+    ///
+    fn write_read(
+        &mut self,
+        code: u8,
+        bytes: &[u8],
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.write(code, bytes)?;
+        self.read(code, buf)
+    }
+}
+
+///
+/// Execute a plan (a [`Plan`](crate::Plan) or any other sequence of
+/// operations, such as a [`FlushPlan`](crate::FlushPlan)) against a bus.
+/// Data read is placed consecutively in `buf`, and the number of bytes read
+/// is returned.
+///
+/// # Panics
+///
+/// Panics if `buf` is too small to hold the data read.
+///
+pub fn execute<B: SpdBus>(
+    bus: &mut B,
+    plan: impl IntoIterator<Item = Operation>,
+    buf: &mut [u8],
+) -> Result<usize, B::Error> {
+    let mut read = 0;
+
+    for op in plan {
+        match op {
+            Operation::Select(select) => {
+                bus.write(select.code, select.bytes())?
+            }
+
+            Operation::Read { code, address, len } => {
+                bus.write_read(code, &[address], &mut buf[read..read + len])?;
+                read += len;
+            }
+
+            Operation::Write { code, address, .. } => {
+                let data = op.data();
+                let mut bytes = [0u8; crate::WRITE_PAGE_SIZE + 1];
+                bytes[0] = address;
+                bytes[1..=data.len()].copy_from_slice(data);
+                bus.write(code, &bytes[..=data.len()])?;
+            }
+        }
+    }
+
+    Ok(read)
+}
@@ -0,0 +1,200 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{classify, DeviceType, Region, DDR5_MAX_SIZE, MAX_SIZE};
+
+const WORDS: usize = DDR5_MAX_SIZE / 64;
+
+///
+/// What the specification says about a byte position.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ByteClass {
+    /// Defined by JEDEC (possibly only for some module types).
+    Defined,
+    /// Reserved; must be coded as zero.
+    Reserved,
+    /// Contents left to the module manufacturer or end user.
+    VendorSpecific,
+}
+
+///
+/// A per-byte classification of an image, stored as bitmaps.
+///
+#[derive(Clone, Debug)]
+pub struct ByteMap {
+    device: DeviceType,
+    size: usize,
+    reserved: [u64; WORDS],
+    vendor: [u64; WORDS],
+}
+
+const fn bitmap(ranges: &[(usize, usize)]) -> [u64; WORDS] {
+    let mut map = [0u64; WORDS];
+    let mut i = 0;
+
+    while i < ranges.len() {
+        let (base, limit) = ranges[i];
+        let mut offset = base;
+
+        while offset <= limit {
+            map[offset / 64] |= 1 << (offset % 64);
+            offset += 1;
+        }
+
+        i += 1;
+    }
+
+    map
+}
+
+///
+/// DDR4 (JEDEC 21-C Annex L).  The manufacturer-specific data (bytes
+/// 353-381) and the areas beyond byte 383 are vendor-specific.
+///
+pub static DDR4_BYTE_MAP: ByteMap = ByteMap {
+    device: DeviceType::Ddr4,
+    size: MAX_SIZE,
+    reserved: bitmap(&[
+        (0x02e, 0x03b),
+        (0x04e, 0x074),
+        (0x100, 0x13f),
+        (0x17e, 0x17f),
+    ]),
+    vendor: bitmap(&[(0x161, 0x17d), (0x180, 0x1ff)]),
+};
+
+///
+/// DDR5 (JESD400-5).  The manufacturer-specific data (bytes 555-639) and
+/// the end-user area (bytes 640-1023) are vendor-specific.
+///
+pub static DDR5_BYTE_MAP: ByteMap = ByteMap {
+    device: DeviceType::Ddr5,
+    size: DDR5_MAX_SIZE,
+    reserved: bitmap(&[
+        (0x00e, 0x00f),
+        (0x01d, 0x01d),
+        (0x05e, 0x0bf),
+        (0x0d6, 0x0e5),
+        (0x1c0, 0x1fd),
+    ]),
+    vendor: bitmap(&[(0x22b, 0x3ff)]),
+};
+
+impl ByteMap {
+    pub fn for_device(device: DeviceType) -> &'static ByteMap {
+        match device {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => &DDR4_BYTE_MAP,
+            DeviceType::Ddr5 => &DDR5_BYTE_MAP,
+        }
+    }
+
+    ///
+    /// The size of the image described.
+    ///
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    ///
+    /// The class of the byte at the given offset, or `None` if the offset is
+    /// beyond the end of the image.
+    ///
+    pub fn class(&self, offset: usize) -> Option<ByteClass> {
+        if offset >= self.size {
+            return None;
+        }
+
+        let bit = 1 << (offset % 64);
+
+        Some(if self.reserved[offset / 64] & bit != 0 {
+            ByteClass::Reserved
+        } else if self.vendor[offset / 64] & bit != 0 {
+            ByteClass::VendorSpecific
+        } else {
+            ByteClass::Defined
+        })
+    }
+
+    ///
+    /// The region containing the byte at the given offset; see
+    /// [`classify`].
+    ///
+    pub fn region(&self, offset: usize) -> Option<Region> {
+        classify(self.device, offset)
+    }
+
+    ///
+    /// Iterate over the offsets of every byte of the given class.
+    ///
+    pub fn offsets(
+        &self,
+        class: ByteClass,
+    ) -> impl Iterator<Item = usize> + '_ {
+        (0..self.size).filter(move |&o| self.class(o) == Some(class))
+    }
+
+    ///
+    /// Iterate over the offsets of reserved bytes that are not zero in the
+    /// given image.
+    ///
+    pub fn reserved_violations<'a>(
+        &'a self,
+        buf: &'a [u8],
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.offsets(ByteClass::Reserved)
+            .filter(move |&o| buf.get(o).is_some_and(|&b| b != 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ddr5, Offset};
+
+    #[test]
+    fn ddr4() {
+        let map = ByteMap::for_device(DeviceType::Ddr4);
+        assert_eq!(map.len(), MAX_SIZE);
+        assert_eq!(
+            map.class(Offset::TAAMin.to_usize()),
+            Some(ByteClass::Defined)
+        );
+        assert_eq!(map.class(0x100), Some(ByteClass::Reserved));
+        assert_eq!(map.class(0x1c0), Some(ByteClass::VendorSpecific));
+        assert_eq!(map.class(0x200), None);
+        assert_eq!(map.region(0x1c0), Some(Region::EndUser));
+        assert_eq!(map.region(0x200), None);
+        assert_eq!(map.offsets(ByteClass::Reserved).count(), 14 + 39 + 64 + 2);
+
+        //
+        // No known field lies in a reserved or vendor-specific byte.
+        //
+        assert!(Offset::iter()
+            .all(|o| map.class(o.to_usize()) == Some(ByteClass::Defined)));
+
+        let mut buf = [0u8; MAX_SIZE];
+        assert_eq!(map.reserved_violations(&buf).next(), None);
+        buf[0x050] = 1;
+        buf[0x1c0] = 1;
+        assert_eq!(map.reserved_violations(&buf).next(), Some(0x050));
+        assert_eq!(map.reserved_violations(&buf).count(), 1);
+    }
+
+    #[test]
+    fn ddr5() {
+        let map = ByteMap::for_device(DeviceType::Ddr5);
+        assert_eq!(map.class(0x3ff), Some(ByteClass::VendorSpecific));
+        assert_eq!(map.class(0x1fe), Some(ByteClass::Defined));
+
+        assert!(ddr5::Offset::iter()
+            .all(|o| map.class(o.to_usize()) == Some(ByteClass::Defined)));
+    }
+}
@@ -0,0 +1,229 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::summary::Identity;
+use crate::{
+    ddr5, update_crc, DeviceType, Error, FromPrimitive, Offset, Picoseconds,
+    Summary, Timing, PART_NUMBER_LEN,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///
+/// The version of [`CompactSpd`] produced by this crate.  Bumped whenever
+/// the layout of the structure changes.
+///
+pub const COMPACT_VERSION: u8 = 1;
+
+///
+/// A compact, fixed-size decoding of an SPD image, suitable for transport
+/// via postcard or hubpack in lieu of the raw image.  All fields are
+/// explicit integers or fixed-size byte arrays, and the structure carries
+/// its own version.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CompactSpd {
+    /// Always [`COMPACT_VERSION`] when produced by this crate.
+    pub version: u8,
+    /// The DRAM device type (byte 2) identifying the generation.
+    pub device_type: u8,
+    pub module_type: u8,
+    pub module_manufacturer: u16,
+    pub dram_manufacturer: u16,
+    pub location: u8,
+    /// Manufacturing year and week, in BCD.
+    pub year: u8,
+    pub week: u8,
+    pub serial: u32,
+    /// Part number, space padded.
+    pub part_number: [u8; PART_NUMBER_LEN],
+    pub module_revision: u8,
+    pub dram_stepping: u8,
+    pub tck_min_ps: u16,
+    pub taa_min_ps: u16,
+    /// Capacity in MiB, or 0 if unknown.  This is informational only and is
+    /// not written back by [`CompactSpd::to_image`].
+    pub capacity_mib: u32,
+}
+
+impl CompactSpd {
+    ///
+    /// Decode a compact representation from an image.  Returns `None` under
+    /// the same conditions as [`Summary::from_spd`].
+    ///
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        let summary = Summary::from_spd(buf)?;
+        let identity = Identity::of(summary.device_type);
+
+        Some(Self {
+            version: COMPACT_VERSION,
+            device_type: summary.device_type as u8,
+            module_type: summary.module_type,
+            module_manufacturer: summary.module_manufacturer,
+            dram_manufacturer: summary.dram_manufacturer,
+            location: buf[identity.location],
+            year: summary.year,
+            week: summary.week,
+            serial: summary.serial,
+            part_number: summary.part_number,
+            module_revision: buf[identity.revision],
+            dram_stepping: buf[identity.dram_stepping],
+            tck_min_ps: summary.tck_min.0 as u16,
+            taa_min_ps: summary.taa_min.0 as u16,
+            capacity_mib: summary.capacity.map_or(0, |c| (c >> 20) as u32),
+        })
+    }
+
+    ///
+    /// Write the fields of this representation into an image, leaving all
+    /// other bytes untouched.  The CRCs are then recomputed.
+    ///
+    pub fn to_image(&self, buf: &mut [u8]) -> Result<(), Error> {
+        if self.version != COMPACT_VERSION {
+            return Err(Error::UnsupportedVersion);
+        }
+
+        let device_type =
+            DeviceType::from_u8(self.device_type).ok_or(Error::TypeMismatch)?;
+
+        let identity = Identity::of(device_type);
+        let len = match device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => crate::MAX_SIZE,
+            DeviceType::Ddr5 => crate::DDR5_MAX_SIZE,
+        };
+
+        if buf.len() < len {
+            return Err(Error::BufferTooSmall);
+        }
+
+        buf[Offset::DRAMDeviceType.to_usize()] = self.device_type;
+        buf[Offset::ModuleType.to_usize()] = self.module_type;
+
+        let m = identity.manufacturer;
+        buf[m..m + 2].copy_from_slice(&self.module_manufacturer.to_le_bytes());
+        let m = identity.dram_manufacturer;
+        buf[m..m + 2].copy_from_slice(&self.dram_manufacturer.to_le_bytes());
+
+        buf[identity.location] = self.location;
+        buf[identity.year] = self.year;
+        buf[identity.year + 1] = self.week;
+
+        let s = identity.serial;
+        buf[s..s + 4].copy_from_slice(&self.serial.to_be_bytes());
+
+        let p = identity.part_number;
+        buf[p..p + identity.part_number_len]
+            .copy_from_slice(&self.part_number[..identity.part_number_len]);
+
+        buf[identity.revision] = self.module_revision;
+        buf[identity.dram_stepping] = self.dram_stepping;
+
+        let tck = Picoseconds(self.tck_min_ps.into());
+        let taa = Picoseconds(self.taa_min_ps.into());
+
+        match device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => {
+                Timing::TCkAvgMin.set(buf, tck).ok_or(Error::ValueTooLong)?;
+                Timing::TAAMin.set(buf, taa).ok_or(Error::ValueTooLong)?;
+                update_crc(buf);
+            }
+            DeviceType::Ddr5 => {
+                ddr5::Timing::TCkAvgMin
+                    .set(buf, tck)
+                    .ok_or(Error::ValueTooLong)?;
+                ddr5::Timing::TAAMin
+                    .set(buf, taa)
+                    .ok_or(Error::ValueTooLong)?;
+                ddr5::update_crc(buf);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    #[test]
+    fn ddr4() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x003] = 0x02;
+        buf[0x012] = 0x06;
+        buf[0x018] = 0x6e;
+        buf[0x140] = 0x80;
+        buf[0x141] = 0xad;
+        buf[0x142] = 0x03;
+        buf[0x143] = 0x22;
+        buf[0x144] = 0x14;
+        buf[0x145..0x149].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        buf[0x149..0x15d].copy_from_slice(b"HMA82GR7DJR8N-XN    ");
+        buf[0x15d] = 0x01;
+        buf[0x160] = 0x02;
+        update_crc(&mut buf);
+
+        let compact = CompactSpd::from_spd(&buf).unwrap();
+        assert_eq!(compact.version, COMPACT_VERSION);
+        assert_eq!(compact.location, 0x03);
+        assert_eq!(compact.module_revision, 0x01);
+        assert_eq!(compact.dram_stepping, 0x02);
+        assert_eq!(compact.tck_min_ps, 750);
+
+        let mut image = [0u8; MAX_SIZE];
+        compact.to_image(&mut image).unwrap();
+        assert_eq!(CompactSpd::from_spd(&image), Some(compact));
+        assert!(crate::verify_crc(&image));
+
+        let old = CompactSpd {
+            version: 0,
+            ..compact
+        };
+        assert_eq!(old.to_image(&mut image), Err(Error::UnsupportedVersion));
+        assert_eq!(
+            compact.to_image(&mut image[..256]),
+            Err(Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn ddr5() {
+        let mut buf = [0u8; crate::DDR5_MAX_SIZE];
+        buf[0x002] = 0x12;
+        buf[0x004] = 0x04;
+        buf[0x006] = 0x20;
+        buf[0x014] = 0xa0;
+        buf[0x015] = 0x01;
+        buf[0x01e] = 0x80;
+        buf[0x01f] = 0x3e;
+        buf[0x0ea] = 0x08;
+        buf[0x0eb] = 0x32;
+        buf[0x202] = 0x07;
+        buf[0x209..0x227].copy_from_slice(b"M321R4GA3BB6-CQK              ");
+        buf[0x22a] = 0x42;
+
+        let compact = CompactSpd::from_spd(&buf).unwrap();
+        assert_eq!(compact.capacity_mib, 32 << 10);
+        assert_eq!(compact.taa_min_ps, 16000);
+
+        let mut image = [0u8; crate::DDR5_MAX_SIZE];
+        compact.to_image(&mut image).unwrap();
+
+        let restored = CompactSpd::from_spd(&image).unwrap();
+        assert_eq!(restored.capacity_mib, 0);
+        assert_eq!(
+            restored,
+            CompactSpd {
+                capacity_mib: 0,
+                ..compact
+            }
+        );
+    }
+}
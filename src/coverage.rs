@@ -0,0 +1,202 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::summary::Identity;
+use crate::{
+    DeviceType, Error, Field, FromPrimitive, Offset, Summary, Value,
+    BLOCK_SIZE, DDR5_MAX_SIZE,
+};
+use core::ops::RangeInclusive;
+
+///
+/// Which 128-byte blocks of an image have been read.  Block granularity
+/// matches both the EE1004 protection blocks and the SPD5118 pages, and
+/// covers images of up to 1024 bytes.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ImageCoverage(u8);
+
+impl ImageCoverage {
+    pub const NONE: ImageCoverage = ImageCoverage(0);
+
+    ///
+    /// Only the first EE1004 page (256 bytes), as read during early boot.
+    ///
+    pub const PAGE0: ImageCoverage = ImageCoverage(0b0011);
+
+    ///
+    /// The coverage of a buffer holding the first `len` bytes of an image.
+    /// A trailing partial block is not considered covered.
+    ///
+    pub fn prefix(len: usize) -> Self {
+        let blocks = (len.min(DDR5_MAX_SIZE) / BLOCK_SIZE) as u32;
+        Self(((1u16 << blocks) - 1) as u8)
+    }
+
+    ///
+    /// Mark the given block as covered, or return `None` if the block is
+    /// beyond the end of the largest image.
+    ///
+    pub fn with_block(self, block: usize) -> Option<Self> {
+        if block < DDR5_MAX_SIZE / BLOCK_SIZE {
+            Some(Self(self.0 | (1 << block)))
+        } else {
+            None
+        }
+    }
+
+    pub fn covers(&self, offset: usize) -> bool {
+        offset < DDR5_MAX_SIZE && self.0 & (1 << (offset / BLOCK_SIZE)) != 0
+    }
+
+    pub fn covers_range(&self, range: RangeInclusive<usize>) -> bool {
+        let (start, end) = range.into_inner();
+        (start / BLOCK_SIZE..=end / BLOCK_SIZE)
+            .all(|block| self.covers(block * BLOCK_SIZE))
+    }
+
+    ///
+    /// Returns true if every byte of the field is covered.
+    ///
+    pub fn covers_field(&self, field: &Field) -> bool {
+        field.offsets().all(|offset| self.covers(offset))
+    }
+}
+
+///
+/// An image of which only some blocks have been read.  Accessors return
+/// [`Error::OffsetNotCovered`] for data that has not been read rather than
+/// panicking or decoding garbage.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct PartialImage<'a> {
+    buf: &'a [u8],
+    coverage: ImageCoverage,
+}
+
+impl<'a> PartialImage<'a> {
+    ///
+    /// Construct from a buffer and the blocks within it that are valid.
+    /// Returns an error if the coverage extends beyond the buffer.
+    ///
+    pub fn new(buf: &'a [u8], coverage: ImageCoverage) -> Result<Self, Error> {
+        if coverage.0 & !ImageCoverage::prefix(buf.len()).0 != 0 {
+            return Err(Error::BufferTooSmall);
+        }
+
+        Ok(Self { buf, coverage })
+    }
+
+    ///
+    /// Construct from a buffer holding the start of an image, such as page 0
+    /// alone.
+    ///
+    pub fn from_prefix(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            coverage: ImageCoverage::prefix(buf.len()),
+        }
+    }
+
+    pub fn coverage(&self) -> ImageCoverage {
+        self.coverage
+    }
+
+    pub fn byte(&self, offset: impl Into<usize>) -> Result<u8, Error> {
+        let offset = offset.into();
+
+        if self.coverage.covers(offset) {
+            Ok(self.buf[offset])
+        } else {
+            Err(Error::OffsetNotCovered)
+        }
+    }
+
+    pub fn get(&self, field: &Field) -> Result<Value<'a>, Error> {
+        if self.coverage.covers_field(field) {
+            Ok(field.get(self.buf))
+        } else {
+            Err(Error::OffsetNotCovered)
+        }
+    }
+
+    pub fn device_type(&self) -> Result<Option<DeviceType>, Error> {
+        self.byte(Offset::DRAMDeviceType).map(DeviceType::from_u8)
+    }
+
+    ///
+    /// Decode a summary, which requires the identity fields and therefore
+    /// (for DDR4) page 1.  Returns [`Error::TypeMismatch`] if the device type
+    /// is not recognized.
+    ///
+    pub fn summary(&self) -> Result<Summary, Error> {
+        let device_type = self.device_type()?.ok_or(Error::TypeMismatch)?;
+        let identity = Identity::of(device_type);
+
+        if !self.coverage.covers_range(0..=identity.dram_stepping) {
+            return Err(Error::OffsetNotCovered);
+        }
+
+        Summary::from_spd(self.buf).ok_or(Error::TypeMismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MAX_SIZE, PAGE_SIZE};
+
+    #[test]
+    fn coverage() {
+        assert_eq!(ImageCoverage::prefix(PAGE_SIZE), ImageCoverage::PAGE0);
+        assert_eq!(ImageCoverage::prefix(300), ImageCoverage::PAGE0);
+        assert!(ImageCoverage::PAGE0.covers(0xff));
+        assert!(!ImageCoverage::PAGE0.covers(0x100));
+        assert!(!ImageCoverage::PAGE0.covers_range(0xf0..=0x10f));
+        let coverage = ImageCoverage::PAGE0.with_block(2).unwrap();
+        assert!(coverage.covers_range(0..=0x17f));
+        assert!(ImageCoverage::NONE.with_block(7).unwrap().covers(0x3ff));
+        assert_eq!(ImageCoverage::NONE.with_block(8), None);
+        assert!(ImageCoverage::prefix(2048).covers(0x3ff));
+        assert!(!ImageCoverage::prefix(2048).covers(0x400));
+    }
+
+    #[test]
+    fn page0() {
+        let mut buf = [0u8; PAGE_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x018] = 0x6e;
+
+        let image = PartialImage::from_prefix(&buf);
+        assert_eq!(image.device_type(), Ok(Some(DeviceType::Ddr4)));
+        assert_eq!(image.byte(Offset::TAAMin), Ok(0x6e));
+        assert_eq!(
+            image.byte(Offset::ModuleManufacturingLocation),
+            Err(Error::OffsetNotCovered)
+        );
+        assert_eq!(
+            image.get(&Field::PART_NUMBER),
+            Err(Error::OffsetNotCovered)
+        );
+        assert_eq!(image.summary(), Err(Error::OffsetNotCovered));
+
+        assert_eq!(
+            PartialImage::new(&buf, ImageCoverage::prefix(MAX_SIZE)).err(),
+            Some(Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn full() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x149..0x14c].copy_from_slice(b"ABC");
+
+        let image = PartialImage::from_prefix(&buf);
+        assert_eq!(image.summary().unwrap().part_number(), b"ABC");
+        assert!(image.get(&Field::PART_NUMBER).is_ok());
+    }
+}
@@ -0,0 +1,136 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::Offset;
+use core::ops::RangeInclusive;
+
+///
+/// The CRC-16 used by SPD:  polynomial 0x1021, initial value 0, unreflected
+/// (also known as CRC-16/XMODEM).
+///
+pub const fn crc16(bytes: &[u8]) -> u16 {
+    crc16_range(bytes, 0, bytes.len())
+}
+
+///
+/// The CRC of `bytes[start..end]`, for use in const contexts where slices
+/// cannot be indexed by a range.
+///
+pub(crate) const fn crc16_range(bytes: &[u8], start: usize, end: usize) -> u16 {
+    let mut crc: u16 = 0;
+    let mut i = start;
+
+    while i < end {
+        crc ^= (bytes[i] as u16) << 8;
+
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+
+        i += 1;
+    }
+
+    crc
+}
+
+///
+/// A range of bytes covered by a CRC, along with the location of the
+/// (little-endian) CRC itself.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrcBlock {
+    pub covered: RangeInclusive<usize>,
+    pub crc: usize,
+}
+
+impl CrcBlock {
+    pub fn compute(&self, buf: &[u8]) -> u16 {
+        crc16(&buf[self.covered.clone()])
+    }
+
+    pub fn stored(&self, buf: &[u8]) -> u16 {
+        u16::from_le_bytes([buf[self.crc], buf[self.crc + 1]])
+    }
+
+    pub fn verify(&self, buf: &[u8]) -> bool {
+        self.compute(buf) == self.stored(buf)
+    }
+
+    pub fn update(&self, buf: &mut [u8]) {
+        let crc = self.compute(buf).to_le_bytes();
+        buf[self.crc..self.crc + 2].copy_from_slice(&crc);
+    }
+}
+
+///
+/// The CRC-protected blocks of a DDR4 SPD:  the base configuration section
+/// (bytes 0-125) and the module-specific section (bytes 128-253).
+///
+pub const fn crc_blocks() -> [CrcBlock; 2] {
+    [
+        CrcBlock {
+            covered: 0..=0x7d,
+            crc: Offset::CRCBaseLSB as usize,
+        },
+        CrcBlock {
+            covered: 0x80..=0xfd,
+            crc: Offset::CRCModuleSpecificLSB as usize,
+        },
+    ]
+}
+
+///
+/// Returns true if all CRCs in the DDR4 image are correct.
+///
+pub fn verify_crc(buf: &[u8]) -> bool {
+    crc_blocks().iter().all(|block| block.verify(buf))
+}
+
+///
+/// Recompute all CRCs in the DDR4 image.
+///
+pub fn update_crc(buf: &mut [u8]) {
+    crc_blocks().iter().for_each(|block| block.update(buf));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    #[test]
+    fn check() {
+        assert_eq!(crc16(b"123456789"), 0x31c3);
+    }
+
+    #[test]
+    fn update() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x000] = 0x23;
+        buf[0x002] = 0x0c;
+        buf[0x080] = 0x11;
+
+        assert!(!verify_crc(&buf));
+        update_crc(&mut buf);
+        assert!(verify_crc(&buf));
+
+        let [block0, block1] = crc_blocks();
+        assert_eq!(block0.stored(&buf), crc16(&buf[0..126]));
+        assert_eq!(block1.stored(&buf), crc16(&buf[128..254]));
+
+        buf[0x0fd] ^= 1;
+        assert!(block0.verify(&buf));
+        assert!(!block1.verify(&buf));
+        assert!(!verify_crc(&buf));
+    }
+}
@@ -0,0 +1,297 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use super::{CasLatencies, Offset, Organization, Timing};
+use crate::{DeviceType, Error, Picoseconds, DDR5_MAX_SIZE};
+
+///
+/// SPD revision 1.0, used for both the base and module-specific sections.
+///
+const SPD_REVISION: u8 = 0x10;
+
+///
+/// Byte 0:  a 1024-byte SPD device, grouped by field.
+///
+#[allow(clippy::unusual_byte_groupings)]
+const SPD_DEVICE_SIZE: u8 = 0b0_011_0000;
+
+///
+/// Bit set in a device type byte to indicate the device is installed.
+///
+const INSTALLED: u8 = 0b1000_0000;
+
+///
+/// The SPD5118 device type, in the low nibble of the hub device type byte.
+///
+const SPD5118: u8 = 0b0000;
+
+///
+/// Bits of the temperature sensor device type byte indicating TS0 and TS1
+/// are installed; the low nibble of 0 denotes a TS5111.
+///
+const TS0: u8 = 0b1000_0000;
+const TS1: u8 = 0b0100_0000;
+
+///
+/// Constructs a DDR5 SPD image from typed parameters.  Bytes not set by the
+/// builder (including all reserved bytes) are zero, and the CRC over the
+/// base configuration and module-specific sections is computed by
+/// [`Builder::build`].  The first parameter that cannot be encoded is
+/// reported by `build`.
+///
+#[derive(Clone, Debug)]
+pub struct Builder {
+    image: [u8; DDR5_MAX_SIZE],
+    error: Option<Error>,
+}
+
+impl Builder {
+    pub fn new(module_type: u8) -> Self {
+        let mut image = [0u8; DDR5_MAX_SIZE];
+
+        image[Offset::SPDDeviceSize.to_usize()] = SPD_DEVICE_SIZE;
+        image[Offset::SPDRevision.to_usize()] = SPD_REVISION;
+        image[Offset::DRAMDeviceType.to_usize()] = DeviceType::Ddr5 as u8;
+        image[Offset::ModuleType.to_usize()] = module_type;
+        image[Offset::ModuleSPDRevision.to_usize()] = SPD_REVISION;
+
+        Self { image, error: None }
+    }
+
+    fn check(mut self, result: Option<()>, error: Error) -> Self {
+        if result.is_none() && self.error.is_none() {
+            self.error = Some(error);
+        }
+
+        self
+    }
+
+    fn set_u16(&mut self, lsb: Offset, value: u16) {
+        let offset = lsb.to_usize();
+        self.image[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn organization(mut self, organization: Organization) -> Self {
+        let result = organization.set(&mut self.image);
+        self.check(result, Error::TypeMismatch)
+    }
+
+    pub fn timing(mut self, timing: Timing, ps: Picoseconds) -> Self {
+        let result = timing.set(&mut self.image, ps);
+        self.check(result, Error::ValueTooLong)
+    }
+
+    ///
+    /// Set the lower limit in clocks of a timing parameter that has one.
+    ///
+    pub fn nck(mut self, timing: Timing, nck: u8) -> Self {
+        let result = timing.nck_offset().map(|offset| {
+            self.image[offset.to_usize()] = nck;
+        });
+        self.check(result, Error::TypeMismatch)
+    }
+
+    pub fn cas_latencies(mut self, cl: CasLatencies) -> Self {
+        let base = Offset::CASLatencies0.to_usize();
+        let limit = Offset::CASLatencies4.to_usize();
+
+        self.image[base..=limit].copy_from_slice(&cl.0.to_le_bytes()[..5]);
+        self.check((cl.0 >> 40 == 0).then_some(()), Error::ValueTooLong)
+    }
+
+    ///
+    /// Describe the SPD hub, which is always an SPD5118.
+    ///
+    pub fn hub(mut self, manufacturer: u16, revision: u8) -> Self {
+        self.set_u16(Offset::SPDHubManufacturerIDCodeLSB, manufacturer);
+        self.image[Offset::SPDHubDeviceType.to_usize()] = INSTALLED | SPD5118;
+        self.image[Offset::SPDHubDeviceRevision.to_usize()] = revision;
+        self
+    }
+
+    ///
+    /// Describe the TS5111 temperature sensors, of which there may be one or
+    /// two.
+    ///
+    pub fn temperature_sensors(
+        mut self,
+        manufacturer: u16,
+        revision: u8,
+        count: u8,
+    ) -> Self {
+        let installed = match count {
+            1 => Some(TS0),
+            2 => Some(TS0 | TS1),
+            _ => None,
+        };
+
+        self.set_u16(
+            Offset::TemperatureSensorManufacturerIDCodeLSB,
+            manufacturer,
+        );
+        self.image[Offset::TemperatureSensorDeviceType.to_usize()] =
+            installed.unwrap_or(0);
+        self.image[Offset::TemperatureSensorDeviceRevision.to_usize()] =
+            revision;
+        self.check(installed.map(|_| ()), Error::TypeMismatch)
+    }
+
+    pub fn manufacturer(mut self, manufacturer: u16) -> Self {
+        self.set_u16(Offset::ModuleManufacturerIDCodeLSB, manufacturer);
+        self
+    }
+
+    pub fn location(mut self, location: u8) -> Self {
+        self.image[Offset::ModuleManufacturingLocation.to_usize()] = location;
+        self
+    }
+
+    ///
+    /// Set the manufacturing date; `year` and `week` are in BCD.
+    ///
+    pub fn date(mut self, year: u8, week: u8) -> Self {
+        self.image[Offset::ModuleManufacturingDateYear.to_usize()] = year;
+        self.image[Offset::ModuleManufacturingDateWeek.to_usize()] = week;
+        self
+    }
+
+    pub fn serial(mut self, serial: u32) -> Self {
+        let base = Offset::ModuleSerialNumber0.to_usize();
+        self.image[base..base + 4].copy_from_slice(&serial.to_be_bytes());
+        self
+    }
+
+    ///
+    /// Set the part number, which is padded with spaces.
+    ///
+    pub fn part_number(mut self, part_number: &[u8]) -> Self {
+        let base = Offset::PartNumberBase.to_usize();
+        let limit = Offset::PartNumberLimit.to_usize();
+        let field = &mut self.image[base..=limit];
+
+        let fits = part_number.len() <= field.len();
+
+        if fits {
+            field.fill(b' ');
+            field[..part_number.len()].copy_from_slice(part_number);
+        }
+
+        self.check(fits.then_some(()), Error::ValueTooLong)
+    }
+
+    pub fn revision(mut self, revision: u8) -> Self {
+        self.image[Offset::ModuleRevisionCode.to_usize()] = revision;
+        self
+    }
+
+    pub fn dram_manufacturer(
+        mut self,
+        manufacturer: u16,
+        stepping: u8,
+    ) -> Self {
+        self.set_u16(Offset::DRAMManufacturerIDCodeLSB, manufacturer);
+        self.image[Offset::DRAMStepping.to_usize()] = stepping;
+        self
+    }
+
+    ///
+    /// Compute the CRC and return the image, or the first error encountered.
+    ///
+    pub fn build(self) -> Result<[u8; DDR5_MAX_SIZE], Error> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let mut image = self.image;
+        super::update_crc(&mut image);
+
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddr5::Sdram;
+    use crate::{crc16, Summary};
+
+    fn rdimm() -> Organization {
+        Organization {
+            channels: 2,
+            bus_width: 32,
+            ecc_width: 8,
+            ranks: 2,
+            first: Sdram {
+                density: 16,
+                dies: 1,
+                width: 8,
+            },
+            second: None,
+        }
+    }
+
+    #[test]
+    fn build() {
+        let image = Builder::new(0x01)
+            .organization(rdimm())
+            .timing(Timing::TCkAvgMin, Picoseconds(416))
+            .timing(Timing::TAAMin, Picoseconds(16000))
+            .timing(Timing::TRFC1Min, Picoseconds(295_000))
+            .nck(Timing::TRRDLMin, 8)
+            .cas_latencies(CasLatencies(0b1_0101))
+            .hub(0x3286, 0x12)
+            .temperature_sensors(0x3286, 0x11, 2)
+            .manufacturer(0xce80)
+            .date(0x23, 0x14)
+            .serial(0x0102_0304)
+            .part_number(b"M321R4GA3BB6-CQK")
+            .dram_manufacturer(0xce80, 0x42)
+            .build()
+            .unwrap();
+
+        assert_eq!(&image[..4], &[0x30, 0x10, 0x12, 0x01]);
+        assert_eq!(Organization::from_spd(&image), Some(rdimm()));
+        assert_eq!(Timing::TRFC1Min.within(&image), Picoseconds(295_000));
+        assert_eq!(Timing::TRRDLMin.nck_within(&image), Some(8));
+        assert!(CasLatencies::from_spd(&image).supports(24));
+        assert_eq!(&image[0xc2..0xc6], &[0x86, 0x32, 0x80, 0x12]);
+        assert_eq!(image[0xd4], 0xc0);
+
+        let summary = Summary::from_spd(&image).unwrap();
+        assert_eq!(summary.capacity, Some(32 << 30));
+        assert_eq!(summary.serial, 0x0102_0304);
+        assert_eq!(summary.part_number(), b"M321R4GA3BB6-CQK");
+
+        let crc = crc16(&image[..0x1fe]);
+        assert_eq!(&image[0x1fe..0x200], &crc.to_le_bytes());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Builder::new(0x01)
+                .timing(Timing::TRFC1Min, Picoseconds(295_500))
+                .part_number(&[b'X'; 31])
+                .build(),
+            Err(Error::ValueTooLong)
+        );
+
+        let organization = Organization {
+            ranks: 0,
+            ..rdimm()
+        };
+
+        assert_eq!(
+            Builder::new(0x01).organization(organization).build(),
+            Err(Error::TypeMismatch)
+        );
+
+        assert_eq!(
+            Builder::new(0x01).nck(Timing::TAAMin, 1).build(),
+            Err(Error::TypeMismatch)
+        );
+    }
+}
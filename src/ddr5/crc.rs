@@ -0,0 +1,69 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use super::Offset;
+use crate::CrcBlock;
+
+///
+/// The single CRC-protected block of a DDR5 SPD:  bytes 0-509 of the first
+/// half, with the CRC in bytes 510 and 511.  Unlike DDR4, the base
+/// configuration and module-specific sections share one CRC.  The
+/// manufacturing information (bytes 512-639) and the end-user area are not
+/// covered by any CRC, so they may be reprogrammed without a CRC update.
+///
+pub const fn crc_block() -> CrcBlock {
+    CrcBlock {
+        covered: 0..=CRC_END - 1,
+        crc: CRC_END,
+    }
+}
+
+///
+/// The end of the covered range, which is also the location of the CRC.
+///
+const CRC_END: usize = Offset::CRCLSB as usize;
+
+///
+/// Returns true if the CRC in the DDR5 image is correct.
+///
+pub fn verify_crc(buf: &[u8]) -> bool {
+    crc_block().verify(buf)
+}
+
+///
+/// Recompute the CRC in the DDR5 image.
+///
+pub fn update_crc(buf: &mut [u8]) {
+    crc_block().update(buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DDR5_MAX_SIZE;
+
+    #[test]
+    fn update() {
+        let mut buf = [0u8; DDR5_MAX_SIZE];
+        buf[0x002] = 0x12;
+        buf[0x1fd] = 0x01;
+
+        assert!(!verify_crc(&buf));
+        update_crc(&mut buf);
+        assert!(verify_crc(&buf));
+
+        //
+        // The manufacturing information is not covered.
+        //
+        buf[0x200] = 0xff;
+        assert!(verify_crc(&buf));
+
+        //
+        // A DDR4 CRC computed over the same image covers only bytes 0-125.
+        //
+        assert!(!crate::crc_blocks()[0].verify(&buf));
+    }
+}
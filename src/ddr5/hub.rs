@@ -0,0 +1,411 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::tse2004::{Resolution, Temperature};
+use crate::FromPrimitive;
+
+///
+/// The mode registers of an SPD5118 hub, as described in JESD300-5.
+///
+#[derive(Copy, Clone, Debug, PartialEq, FromPrimitive)]
+pub enum Register {
+    DeviceTypeMSB = 0,
+    DeviceTypeLSB = 1,
+    DeviceRevision = 2,
+    VendorIDLSB = 3,
+    VendorIDMSB = 4,
+    LegacyModeConfiguration = 11,
+    WriteProtection0 = 12,
+    WriteProtection1 = 13,
+    ClearThermalStatus = 19,
+    ClearErrorStatus = 20,
+    InterruptConfiguration = 27,
+    HighLimitLSB = 28,
+    HighLimitMSB = 29,
+    LowLimitLSB = 30,
+    LowLimitMSB = 31,
+    CriticalHighLimitLSB = 32,
+    CriticalHighLimitMSB = 33,
+    CriticalLowLimitLSB = 34,
+    CriticalLowLimitMSB = 35,
+    DeviceStatus = 48,
+    TemperatureLSB = 49,
+    TemperatureMSB = 50,
+    ThermalStatus = 51,
+    ErrorStatus = 52,
+}
+
+impl Register {
+    ///
+    /// The bytes of a write of `value` to this register.
+    ///
+    pub fn write(self, value: u8) -> [u8; 2] {
+        [self as u8, value]
+    }
+}
+
+///
+/// The kind of hub, from the device type in MR0 and MR1.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HubKind {
+    /// An SPD5118, with an integrated temperature sensor.
+    Spd5118,
+    /// An SPD5108, without a temperature sensor.
+    Spd5108,
+    Other(u16),
+}
+
+///
+/// The identity of an SPD hub, as read from MR0 to MR4.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HubIdentity {
+    pub kind: HubKind,
+    /// Major and minor device revision.
+    pub revision: (u8, u8),
+    /// JEP106 ID of the vendor, continuation count in the low byte.
+    pub vendor: u16,
+}
+
+impl HubIdentity {
+    pub fn decode(mr: [u8; 5]) -> Self {
+        Self {
+            kind: match u16::from_be_bytes([mr[0], mr[1]]) {
+                0x5118 => HubKind::Spd5118,
+                0x5108 => HubKind::Spd5108,
+                kind => HubKind::Other(kind),
+            },
+            revision: (((mr[2] >> 4) & 0b11) + 1, (mr[2] >> 1) & 0b111),
+            vendor: u16::from_le_bytes([mr[3], mr[4]]),
+        }
+    }
+
+    ///
+    /// The name of the vendor, if known.
+    ///
+    pub fn vendor_name(&self) -> Option<&'static str> {
+        crate::manufacturer_name(self.vendor)
+    }
+}
+
+///
+/// The device status register (MR48).
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DeviceStatus {
+    /// Write protection is overridden (the HSA pin is tied low), allowing
+    /// writes to protected blocks.
+    pub write_protect_override: bool,
+    /// A write to the NVM is in progress.
+    pub nvm_busy: bool,
+}
+
+impl DeviceStatus {
+    pub fn decode(mr48: u8) -> Self {
+        Self {
+            write_protect_override: mr48 & (1 << 2) != 0,
+            nvm_busy: mr48 & (1 << 3) != 0,
+        }
+    }
+}
+
+///
+/// The thermal sensor status register (MR51), each bit of which is cleared
+/// by writing a one to the same bit of MR19.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ThermalStatus {
+    pub above_high: bool,
+    pub below_low: bool,
+    pub above_critical_high: bool,
+    pub below_critical_low: bool,
+}
+
+impl ThermalStatus {
+    pub fn decode(mr51: u8) -> Self {
+        Self {
+            above_high: mr51 & (1 << 0) != 0,
+            below_low: mr51 & (1 << 1) != 0,
+            above_critical_high: mr51 & (1 << 2) != 0,
+            below_critical_low: mr51 & (1 << 3) != 0,
+        }
+    }
+
+    pub fn encode(&self) -> u8 {
+        (self.above_high as u8)
+            | (self.below_low as u8) << 1
+            | (self.above_critical_high as u8) << 2
+            | (self.below_critical_low as u8) << 3
+    }
+
+    pub fn any(&self) -> bool {
+        self.encode() != 0
+    }
+
+    ///
+    /// The write that clears the conditions set in this status.
+    ///
+    pub fn clear(&self) -> [u8; 2] {
+        Register::ClearThermalStatus.write(self.encode())
+    }
+}
+
+///
+/// The error status register (MR52), each bit of which is cleared by
+/// writing a one to the same bit of MR20.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ErrorStatus {
+    /// An I3C parity error was detected.
+    pub parity: bool,
+    /// A packet error code mismatch was detected.
+    pub pec: bool,
+}
+
+impl ErrorStatus {
+    pub fn decode(mr52: u8) -> Self {
+        Self {
+            parity: mr52 & (1 << 0) != 0,
+            pec: mr52 & (1 << 1) != 0,
+        }
+    }
+
+    pub fn encode(&self) -> u8 {
+        (self.parity as u8) | (self.pec as u8) << 1
+    }
+
+    pub fn any(&self) -> bool {
+        self.encode() != 0
+    }
+
+    ///
+    /// The write that clears the errors set in this status.
+    ///
+    pub fn clear(&self) -> [u8; 2] {
+        Register::ClearErrorStatus.write(self.encode())
+    }
+}
+
+///
+/// The interrupt configuration register (MR27):  which conditions raise an
+/// in-band interrupt.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct InterruptConfiguration {
+    pub thermal: ThermalStatus,
+    pub error: bool,
+}
+
+impl InterruptConfiguration {
+    ///
+    /// Bit of MR27 that, when written, clears all pending events.
+    ///
+    const CLEAR_GLOBAL: u8 = 1 << 7;
+
+    pub fn decode(mr27: u8) -> Self {
+        Self {
+            thermal: ThermalStatus::decode(mr27),
+            error: mr27 & (1 << 4) != 0,
+        }
+    }
+
+    pub fn encode(&self) -> u8 {
+        self.thermal.encode() | (self.error as u8) << 4
+    }
+
+    ///
+    /// The write that clears all pending events while leaving this
+    /// configuration in place.
+    ///
+    pub fn clear_all(&self) -> [u8; 2] {
+        Register::InterruptConfiguration
+            .write(self.encode() | Self::CLEAR_GLOBAL)
+    }
+
+    ///
+    /// The enabled causes among the given thermal and error status.
+    ///
+    pub fn pending(
+        &self,
+        thermal: ThermalStatus,
+        error: ErrorStatus,
+    ) -> PendingInterrupts {
+        PendingInterrupts {
+            thermal: ThermalStatus::decode(
+                self.thermal.encode() & thermal.encode(),
+            ),
+            error: ErrorStatus::decode(if self.error {
+                error.encode()
+            } else {
+                0
+            }),
+        }
+    }
+}
+
+///
+/// The causes of a pending interrupt.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PendingInterrupts {
+    pub thermal: ThermalStatus,
+    pub error: ErrorStatus,
+}
+
+impl PendingInterrupts {
+    pub fn any(&self) -> bool {
+        self.thermal.any() || self.error.any()
+    }
+
+    ///
+    /// The writes that clear exactly the pending causes, leaving any other
+    /// conditions latched.
+    ///
+    pub fn clear(&self) -> [[u8; 2]; 2] {
+        [self.thermal.clear(), self.error.clear()]
+    }
+}
+
+///
+/// Decode the temperature in MR49 and MR50, which has the same format as
+/// that of a TSE2004 with a resolution of a quarter of a degree.
+///
+pub fn decode_temperature(mr49: u8, mr50: u8) -> Temperature {
+    Temperature::decode_with(
+        u16::from_le_bytes([mr49, mr50]),
+        Resolution::QuarterDegree,
+    )
+}
+
+///
+/// The limits of the thermal sensor (MR28 to MR35), each encoded as the
+/// temperature is.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ThermalLimits {
+    pub high: Temperature,
+    pub low: Temperature,
+    pub critical_high: Temperature,
+    pub critical_low: Temperature,
+}
+
+impl ThermalLimits {
+    ///
+    /// The writes needed to program the limits.  Returns `None` if any limit
+    /// cannot be represented.
+    ///
+    pub fn writes(&self) -> Option<[[u8; 2]; 8]> {
+        let [hl, hm] = self.high.encode_limit()?.to_le_bytes();
+        let [ll, lm] = self.low.encode_limit()?.to_le_bytes();
+        let [chl, chm] = self.critical_high.encode_limit()?.to_le_bytes();
+        let [cll, clm] = self.critical_low.encode_limit()?.to_le_bytes();
+
+        Some([
+            Register::HighLimitLSB.write(hl),
+            Register::HighLimitMSB.write(hm),
+            Register::LowLimitLSB.write(ll),
+            Register::LowLimitMSB.write(lm),
+            Register::CriticalHighLimitLSB.write(chl),
+            Register::CriticalHighLimitMSB.write(chm),
+            Register::CriticalLowLimitLSB.write(cll),
+            Register::CriticalLowLimitMSB.write(clm),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity() {
+        let id = HubIdentity::decode([0x51, 0x18, 0b0001_0010, 0x86, 0x32]);
+        assert_eq!(id.kind, HubKind::Spd5118);
+        assert_eq!(id.revision, (2, 1));
+        assert_eq!(id.vendor_name(), Some("Montage Technology"));
+
+        let id = HubIdentity::decode([0x51, 0x08, 0, 0x80, 0xb3]);
+        assert_eq!(id.kind, HubKind::Spd5108);
+        assert_eq!(id.revision, (1, 0));
+        assert_eq!(id.vendor_name(), Some("Renesas (IDT)"));
+        assert_eq!(HubIdentity::decode([0; 5]).kind, HubKind::Other(0));
+    }
+
+    #[test]
+    fn status() {
+        let status = DeviceStatus::decode(0b0000_1100);
+        assert!(status.write_protect_override);
+        assert!(status.nvm_busy);
+        assert!(!DeviceStatus::decode(0).nvm_busy);
+
+        let thermal = ThermalStatus::decode(0b0000_0101);
+        assert!(thermal.above_high && thermal.above_critical_high);
+        assert!(!thermal.below_low);
+        assert_eq!(thermal.clear(), [19, 0b0000_0101]);
+
+        let error = ErrorStatus::decode(0b10);
+        assert!(error.pec && !error.parity);
+        assert_eq!(error.clear(), [20, 0b10]);
+    }
+
+    #[test]
+    fn interrupts() {
+        let config = InterruptConfiguration {
+            thermal: ThermalStatus {
+                above_critical_high: true,
+                ..Default::default()
+            },
+            error: false,
+        };
+        assert_eq!(config.encode(), 0b0000_0100);
+        assert_eq!(InterruptConfiguration::decode(0b0000_0100), config);
+        assert_eq!(config.clear_all(), [27, 0b1000_0100]);
+
+        let pending = config.pending(
+            ThermalStatus::decode(0b0000_0101),
+            ErrorStatus::decode(0b11),
+        );
+        assert!(pending.any());
+        assert_eq!(pending.thermal.encode(), 0b0000_0100);
+        assert!(!pending.error.any());
+        assert_eq!(pending.clear(), [[19, 0b0000_0100], [20, 0]]);
+
+        let pending = config.pending(
+            ThermalStatus::decode(0b0000_0001),
+            ErrorStatus::default(),
+        );
+        assert!(!pending.any());
+    }
+
+    #[test]
+    fn thermal() {
+        assert_eq!(
+            decode_temperature(0x90, 0x01),
+            Temperature::from_celsius(25)
+        );
+        assert_eq!(decode_temperature(0xfe, 0x1f), Temperature(-4));
+
+        let limits = ThermalLimits {
+            high: Temperature::from_celsius(85),
+            low: Temperature::from_celsius(0),
+            critical_high: Temperature::from_celsius(95),
+            critical_low: Temperature::from_celsius(-10),
+        };
+
+        let writes = limits.writes().unwrap();
+        assert_eq!(writes[..2], [[28, 0x50], [29, 0x05]]);
+        assert_eq!(writes[6..], [[34, 0x60], [35, 0x1f]]);
+        assert_eq!(
+            ThermalLimits {
+                low: Temperature(1),
+                ..limits
+            }
+            .writes(),
+            None
+        );
+    }
+}
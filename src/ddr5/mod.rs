@@ -0,0 +1,163 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+//!
+//! DDR5 SPD layout and decoding, as described in JESD400-5.
+//!
+
+use crate::{offsets, FromPrimitive};
+
+mod builder;
+mod crc;
+mod hub;
+mod module;
+mod organization;
+mod protection;
+mod timing;
+
+pub use builder::Builder;
+pub use crc::{crc_block, update_crc, verify_crc};
+pub use hub::{
+    decode_temperature, DeviceStatus, ErrorStatus, HubIdentity, HubKind,
+    InterruptConfiguration, PendingInterrupts, Register, ThermalLimits,
+    ThermalStatus,
+};
+pub use module::{mechanical, Attributes, OperatingTemperature};
+pub use organization::{Organization, Sdram};
+pub use protection::{
+    BlockProtection, OverrideError, OverrideSequence, Step,
+    PROTECTION_BLOCK_SIZE,
+};
+pub use timing::{clocks, CasLatencies, Timing};
+
+offsets! { Offset {
+    SPDDeviceSize = 0x000,
+    SPDRevision = 0x001,
+    DRAMDeviceType = 0x002,
+    ModuleType = 0x003,
+    FirstSDRAMDensity = 0x004,
+    FirstSDRAMAddressing = 0x005,
+    FirstSDRAMIOWidth = 0x006,
+    FirstSDRAMBankGroups = 0x007,
+    SecondSDRAMDensity = 0x008,
+    SecondSDRAMAddressing = 0x009,
+    SecondSDRAMIOWidth = 0x00a,
+    SecondSDRAMBankGroups = 0x00b,
+    SDRAMOptionalFeatures = 0x00c,
+    ThermalRefreshOptions = 0x00d,
+    NominalVoltageVDD = 0x010,
+    NominalVoltageVDDQ = 0x011,
+    NominalVoltageVPP = 0x012,
+    TimingMode = 0x013,
+    TCkAvgMinLSB = 0x014,
+    TCkAvgMinMSB = 0x015,
+    TCkAvgMaxLSB = 0x016,
+    TCkAvgMaxMSB = 0x017,
+    CASLatencies0 = 0x018,
+    CASLatencies1 = 0x019,
+    CASLatencies2 = 0x01a,
+    CASLatencies3 = 0x01b,
+    CASLatencies4 = 0x01c,
+    TAAMinLSB = 0x01e,
+    TAAMinMSB = 0x01f,
+    TRCDMinLSB = 0x020,
+    TRCDMinMSB = 0x021,
+    TRPMinLSB = 0x022,
+    TRPMinMSB = 0x023,
+    TRASMinLSB = 0x024,
+    TRASMinMSB = 0x025,
+    TRCMinLSB = 0x026,
+    TRCMinMSB = 0x027,
+    TWRMinLSB = 0x028,
+    TWRMinMSB = 0x029,
+    TRFC1MinLSB = 0x02a,
+    TRFC1MinMSB = 0x02b,
+    TRFC2MinLSB = 0x02c,
+    TRFC2MinMSB = 0x02d,
+    TRFCsbMinLSB = 0x02e,
+    TRFCsbMinMSB = 0x02f,
+    TRRDLMinLSB = 0x046,
+    TRRDLMinMSB = 0x047,
+    TRRDLMinNCK = 0x048,
+    TCCDLMinLSB = 0x049,
+    TCCDLMinMSB = 0x04a,
+    TCCDLMinNCK = 0x04b,
+    TCCDLWRMinLSB = 0x04c,
+    TCCDLWRMinMSB = 0x04d,
+    TCCDLWRMinNCK = 0x04e,
+    TCCDLWR2MinLSB = 0x04f,
+    TCCDLWR2MinMSB = 0x050,
+    TCCDLWR2MinNCK = 0x051,
+    TFAWMinLSB = 0x052,
+    TFAWMinMSB = 0x053,
+    TFAWMinNCK = 0x054,
+    TCCDLWTRMinLSB = 0x055,
+    TCCDLWTRMinMSB = 0x056,
+    TCCDLWTRMinNCK = 0x057,
+    TCCDSWTRMinLSB = 0x058,
+    TCCDSWTRMinMSB = 0x059,
+    TCCDSWTRMinNCK = 0x05a,
+    TRTPMinLSB = 0x05b,
+    TRTPMinMSB = 0x05c,
+    TRTPMinNCK = 0x05d,
+    ModuleSPDRevision = 0x0c0,
+    SPDHubManufacturerIDCodeLSB = 0x0c2,
+    SPDHubManufacturerIDCodeMSB = 0x0c3,
+    SPDHubDeviceType = 0x0c4,
+    SPDHubDeviceRevision = 0x0c5,
+    TemperatureSensorManufacturerIDCodeLSB = 0x0d2,
+    TemperatureSensorManufacturerIDCodeMSB = 0x0d3,
+    TemperatureSensorDeviceType = 0x0d4,
+    TemperatureSensorDeviceRevision = 0x0d5,
+    ModuleNominalHeight = 0x0e6,
+    ModuleMaximumThickness = 0x0e7,
+    ReferenceRawCard = 0x0e8,
+    DIMMAttributes = 0x0e9,
+    ModuleOrganization = 0x0ea,
+    MemoryChannelBusWidth = 0x0eb,
+    CRCLSB = 0x1fe,
+    CRCMSB = 0x1ff,
+    ModuleManufacturerIDCodeLSB = 0x200,
+    ModuleManufacturerIDCodeMSB = 0x201,
+    ModuleManufacturingLocation = 0x202,
+    ModuleManufacturingDateYear = 0x203,
+    ModuleManufacturingDateWeek = 0x204,
+    ModuleSerialNumber0 = 0x205,
+    ModuleSerialNumber1 = 0x206,
+    ModuleSerialNumber2 = 0x207,
+    ModuleSerialNumber3 = 0x208,
+    PartNumberBase = 0x209,
+    PartNumberLimit = 0x226,
+    ModuleRevisionCode = 0x227,
+    DRAMManufacturerIDCodeLSB = 0x228,
+    DRAMManufacturerIDCodeMSB = 0x229,
+    DRAMStepping = 0x22a,
+} ranges {
+    PartNumberBase..=PartNumberLimit,
+}}
+
+impl Offset {
+    pub fn to_usize(self) -> usize {
+        self as usize
+    }
+
+    pub fn within(self, buf: &[u8]) -> u8 {
+        buf[self as usize]
+    }
+
+    pub fn iter() -> impl Iterator<Item = Offset> {
+        Self::ALL.iter().copied()
+    }
+
+    ///
+    /// Read the little-endian 16-bit value whose least significant byte is
+    /// at this offset.
+    ///
+    pub fn within_u16(self, buf: &[u8]) -> u16 {
+        let offset = self as usize;
+        u16::from_le_bytes([buf[offset], buf[offset + 1]])
+    }
+}
@@ -0,0 +1,147 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use super::Offset;
+use crate::Mechanical;
+
+///
+/// The operating temperature range of a module, from bits 7:4 of the DIMM
+/// attributes byte (byte 233).
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OperatingTemperature {
+    /// A1T:  -40 to 125 °C.
+    A1T,
+    /// A2T:  -40 to 105 °C.
+    A2T,
+    /// A3T:  -40 to 85 °C.
+    A3T,
+    /// IT:  -40 to 95 °C.
+    IT,
+    /// ST:  -25 to 85 °C.
+    ST,
+    /// ET:  -25 to 105 °C.
+    ET,
+    /// RT:  0 to 45 °C.
+    RT,
+    /// NT:  0 to 85 °C.
+    NT,
+    /// XT:  0 to 95 °C.
+    XT,
+    /// A reserved encoding.
+    Reserved(u8),
+}
+
+impl OperatingTemperature {
+    pub fn decode(grade: u8) -> Self {
+        match grade {
+            0b0000 => OperatingTemperature::A1T,
+            0b0001 => OperatingTemperature::A2T,
+            0b0010 => OperatingTemperature::A3T,
+            0b0011 => OperatingTemperature::IT,
+            0b0100 => OperatingTemperature::ST,
+            0b0101 => OperatingTemperature::ET,
+            0b0110 => OperatingTemperature::RT,
+            0b0111 => OperatingTemperature::NT,
+            0b1000 => OperatingTemperature::XT,
+            _ => OperatingTemperature::Reserved(grade),
+        }
+    }
+
+    ///
+    /// The minimum and maximum operating temperatures in degrees Celsius, or
+    /// `None` for a reserved encoding.
+    ///
+    pub fn range(&self) -> Option<(i16, i16)> {
+        match *self {
+            OperatingTemperature::A1T => Some((-40, 125)),
+            OperatingTemperature::A2T => Some((-40, 105)),
+            OperatingTemperature::A3T => Some((-40, 85)),
+            OperatingTemperature::IT => Some((-40, 95)),
+            OperatingTemperature::ST => Some((-25, 85)),
+            OperatingTemperature::ET => Some((-25, 105)),
+            OperatingTemperature::RT => Some((0, 45)),
+            OperatingTemperature::NT => Some((0, 85)),
+            OperatingTemperature::XT => Some((0, 95)),
+            OperatingTemperature::Reserved(_) => None,
+        }
+    }
+}
+
+///
+/// The DIMM attributes, as described in byte 233.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Attributes {
+    /// The number of rows of DRAM packages on each side of the module, if
+    /// defined.
+    pub rows: Option<u8>,
+    pub heat_spreader: bool,
+    pub temperature: OperatingTemperature,
+}
+
+impl Attributes {
+    pub fn from_spd(buf: &[u8]) -> Self {
+        Self::decode(Offset::DIMMAttributes.within(buf))
+    }
+
+    pub fn decode(attributes: u8) -> Self {
+        Self {
+            rows: match attributes & 0b11 {
+                0b01 => Some(1),
+                0b10 => Some(2),
+                _ => None,
+            },
+            heat_spreader: attributes & 0b100 != 0,
+            temperature: OperatingTemperature::decode(attributes >> 4),
+        }
+    }
+}
+
+///
+/// The module's mechanical parameters, which are encoded in bytes 230 and
+/// 231 just as they are in bytes 128 and 129 of a DDR4 SPD.
+///
+pub fn mechanical(buf: &[u8]) -> Mechanical {
+    Mechanical::decode(
+        Offset::ModuleNominalHeight.within(buf),
+        Offset::ModuleMaximumThickness.within(buf),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DDR5_MAX_SIZE;
+
+    #[test]
+    fn decode() {
+        let mut buf = [0u8; DDR5_MAX_SIZE];
+        buf[0x0e6] = 0x0f;
+        buf[0x0e7] = 0x22;
+        buf[0x0e9] = 0b0011_0110;
+
+        let attributes = Attributes::from_spd(&buf);
+        assert_eq!(attributes.rows, Some(2));
+        assert!(attributes.heat_spreader);
+        assert_eq!(attributes.temperature, OperatingTemperature::IT);
+        assert_eq!(attributes.temperature.range(), Some((-40, 95)));
+
+        let mechanical = mechanical(&buf);
+        assert!(mechanical.fits_height(31));
+        assert!(mechanical.fits_thickness(3, 3));
+        assert!(!mechanical.fits_thickness(2, 3));
+
+        let attributes = Attributes::decode(0b1111_0011);
+        assert_eq!(attributes.rows, None);
+        assert!(!attributes.heat_spreader);
+        assert_eq!(
+            attributes.temperature,
+            OperatingTemperature::Reserved(0b1111)
+        );
+        assert_eq!(attributes.temperature.range(), None);
+    }
+}
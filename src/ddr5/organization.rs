@@ -0,0 +1,336 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use super::Offset;
+
+///
+/// The characteristics of one of the (up to) two kinds of SDRAM on a DDR5
+/// module, as described by bytes 4-7 (first) or 8-11 (second).
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sdram {
+    /// Density per die, in gigabits.
+    pub density: u32,
+    /// Dies per package.
+    pub dies: u8,
+    /// I/O width in bits (4, 8, 16 or 32).
+    pub width: u8,
+}
+
+impl Sdram {
+    ///
+    /// Decode from the density/package byte and the I/O width byte.  Returns
+    /// `None` for reserved encodings (including a density of 0, which denotes
+    /// the absence of a second SDRAM type).
+    ///
+    pub fn decode(density: u8, width: u8) -> Option<Self> {
+        let dies = match density >> 5 {
+            0b000 => 1,
+            0b010 => 2,
+            0b011 => 4,
+            0b100 => 8,
+            0b101 => 16,
+            _ => return None,
+        };
+
+        let density = match density & 0b1_1111 {
+            0b0_0001 => 4,
+            0b0_0010 => 8,
+            0b0_0011 => 12,
+            0b0_0100 => 16,
+            0b0_0101 => 24,
+            0b0_0110 => 32,
+            0b0_0111 => 48,
+            0b0_1000 => 64,
+            _ => return None,
+        };
+
+        let width = match width >> 5 {
+            0b000 => 4,
+            0b001 => 8,
+            0b010 => 16,
+            0b011 => 32,
+            _ => return None,
+        };
+
+        Some(Self {
+            density,
+            dies,
+            width,
+        })
+    }
+
+    ///
+    /// Encode into the density/package byte and the I/O width byte; the
+    /// inverse of [`Sdram::decode`].  Returns `None` if the SDRAM cannot be
+    /// described.
+    ///
+    pub fn encode(&self) -> Option<(u8, u8)> {
+        let dies = match self.dies {
+            1 => 0b000,
+            2 => 0b010,
+            4 => 0b011,
+            8 => 0b100,
+            16 => 0b101,
+            _ => return None,
+        };
+
+        let density = match self.density {
+            4 => 0b0_0001,
+            8 => 0b0_0010,
+            12 => 0b0_0011,
+            16 => 0b0_0100,
+            24 => 0b0_0101,
+            32 => 0b0_0110,
+            48 => 0b0_0111,
+            64 => 0b0_1000,
+            _ => return None,
+        };
+
+        let width = match self.width {
+            4 => 0b000,
+            8 => 0b001,
+            16 => 0b010,
+            32 => 0b011,
+            _ => return None,
+        };
+
+        Some(((dies << 5) | density, width << 5))
+    }
+}
+
+///
+/// The organization of a DDR5 module.  Unlike DDR4, a DDR5 DIMM has two
+/// independent subchannels, each with its own primary bus and ECC bits;
+/// `bus_width` and `ecc_width` are per channel.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Organization {
+    /// Channels (subchannels) per DIMM.
+    pub channels: u8,
+    /// Primary bus width per channel, in bits.
+    pub bus_width: u8,
+    /// Bus width extension (ECC) per channel, in bits.
+    pub ecc_width: u8,
+    /// Package ranks per channel.
+    pub ranks: u8,
+    /// The SDRAM used for even ranks (or all ranks, if symmetrical).
+    pub first: Sdram,
+    /// The SDRAM used for odd ranks on an asymmetrical module.
+    pub second: Option<Sdram>,
+}
+
+impl Organization {
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        let organization = Offset::ModuleOrganization.within(buf);
+        let bus = Offset::MemoryChannelBusWidth.within(buf);
+
+        let channels = match (bus >> 5) & 0b11 {
+            0b00 => 1,
+            0b01 => 2,
+            _ => return None,
+        };
+
+        let bus_width = match bus & 0b111 {
+            0b000 => 8,
+            0b001 => 16,
+            0b010 => 32,
+            0b011 => 64,
+            _ => return None,
+        };
+
+        let ecc_width = match (bus >> 3) & 0b11 {
+            0b00 => 0,
+            0b01 => 4,
+            0b10 => 8,
+            _ => return None,
+        };
+
+        let first = Sdram::decode(
+            Offset::FirstSDRAMDensity.within(buf),
+            Offset::FirstSDRAMIOWidth.within(buf),
+        )?;
+
+        let second = if organization & 0b0100_0000 != 0 {
+            Some(Sdram::decode(
+                Offset::SecondSDRAMDensity.within(buf),
+                Offset::SecondSDRAMIOWidth.within(buf),
+            )?)
+        } else {
+            None
+        };
+
+        Some(Self {
+            channels,
+            bus_width,
+            ecc_width,
+            ranks: ((organization >> 3) & 0b111) + 1,
+            first,
+            second,
+        })
+    }
+
+    ///
+    /// Encode the organization into the image; the inverse of
+    /// [`Organization::from_spd`].  Returns `None` (leaving the image
+    /// untouched) if the organization cannot be described.
+    ///
+    pub fn set(&self, buf: &mut [u8]) -> Option<()> {
+        let channels = match self.channels {
+            1 => 0b00,
+            2 => 0b01,
+            _ => return None,
+        };
+
+        let bus_width = match self.bus_width {
+            8 => 0b000,
+            16 => 0b001,
+            32 => 0b010,
+            64 => 0b011,
+            _ => return None,
+        };
+
+        let ecc_width = match self.ecc_width {
+            0 => 0b00,
+            4 => 0b01,
+            8 => 0b10,
+            _ => return None,
+        };
+
+        if !(1..=8).contains(&self.ranks) {
+            return None;
+        }
+
+        let first = self.first.encode()?;
+        let second = match self.second {
+            Some(second) => second.encode()?,
+            None => (0, 0),
+        };
+
+        let asymmetrical = if self.asymmetrical() { 0b0100_0000 } else { 0 };
+
+        buf[Offset::FirstSDRAMDensity.to_usize()] = first.0;
+        buf[Offset::FirstSDRAMIOWidth.to_usize()] = first.1;
+        buf[Offset::SecondSDRAMDensity.to_usize()] = second.0;
+        buf[Offset::SecondSDRAMIOWidth.to_usize()] = second.1;
+        buf[Offset::ModuleOrganization.to_usize()] =
+            asymmetrical | ((self.ranks - 1) << 3);
+        buf[Offset::MemoryChannelBusWidth.to_usize()] =
+            (channels << 5) | (ecc_width << 3) | bus_width;
+
+        Some(())
+    }
+
+    ///
+    /// Returns true if the module has ranks of differing SDRAM.
+    ///
+    pub fn asymmetrical(&self) -> bool {
+        self.second.is_some()
+    }
+
+    ///
+    /// The SDRAM used for the given package rank.
+    ///
+    pub fn sdram(&self, rank: u8) -> Sdram {
+        match self.second {
+            Some(second) if rank % 2 == 1 => second,
+            _ => self.first,
+        }
+    }
+
+    ///
+    /// Capacity of the module in bytes, excluding ECC:  for each rank, the
+    /// number of packages across the primary bus of each channel, times the
+    /// dies per package, times the density per die.
+    ///
+    pub fn capacity(&self) -> u64 {
+        (0..self.ranks)
+            .map(|rank| {
+                let sdram = self.sdram(rank);
+                let packages = (self.bus_width / sdram.width) as u64;
+                let bits = (sdram.density as u64) << 30;
+
+                self.channels as u64 * packages * sdram.dies as u64 * bits / 8
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)] // literals are grouped by field
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1 << 30;
+
+    fn image(density: u8, width: u8, org: u8, bus: u8) -> [u8; 1024] {
+        let mut buf = [0u8; 1024];
+        buf[Offset::FirstSDRAMDensity.to_usize()] = density;
+        buf[Offset::FirstSDRAMIOWidth.to_usize()] = width;
+        buf[Offset::ModuleOrganization.to_usize()] = org;
+        buf[Offset::MemoryChannelBusWidth.to_usize()] = bus;
+        buf
+    }
+
+    #[test]
+    fn rdimm_2rx8() {
+        //
+        // 32 GiB 2Rx8 RDIMM of 16 Gb monolithic x8 devices:  two 32-bit
+        // channels with 8 bits of ECC each.
+        //
+        let buf = image(0b000_00100, 0b001_00000, 0b00_001_000, 0b0_01_10_010);
+        let org = Organization::from_spd(&buf).unwrap();
+
+        assert_eq!(org.channels, 2);
+        assert_eq!(org.bus_width, 32);
+        assert_eq!(org.ecc_width, 8);
+        assert_eq!(org.ranks, 2);
+        assert_eq!(
+            org.first,
+            Sdram {
+                density: 16,
+                dies: 1,
+                width: 8
+            }
+        );
+        assert!(!org.asymmetrical());
+        assert_eq!(org.capacity(), 32 * GIB);
+    }
+
+    #[test]
+    fn udimm_1rx16() {
+        //
+        // 8 GiB 1Rx16 UDIMM of 16 Gb x16 devices with no ECC.
+        //
+        let buf = image(0b000_00100, 0b010_00000, 0, 0b0_01_00_010);
+        let org = Organization::from_spd(&buf).unwrap();
+        assert_eq!(org.ecc_width, 0);
+        assert_eq!(org.capacity(), 8 * GIB);
+    }
+
+    #[test]
+    fn asymmetrical() {
+        let mut buf =
+            image(0b000_00100, 0b001_00000, 0b01_001_000, 0b0_01_10_010);
+        buf[Offset::SecondSDRAMDensity.to_usize()] = 0b000_00010;
+        buf[Offset::SecondSDRAMIOWidth.to_usize()] = 0b001_00000;
+
+        let org = Organization::from_spd(&buf).unwrap();
+        assert!(org.asymmetrical());
+        assert_eq!(org.sdram(1).density, 8);
+        assert_eq!(org.capacity(), 16 * GIB + 8 * GIB);
+
+        let mut encoded = [0u8; 1024];
+        org.set(&mut encoded).unwrap();
+        assert_eq!(&encoded[..], &buf[..]);
+    }
+
+    #[test]
+    fn reserved() {
+        let buf = image(0, 0, 0, 0b0_01_00_010);
+        assert_eq!(Organization::from_spd(&buf), None);
+    }
+}
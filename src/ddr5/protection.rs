@@ -0,0 +1,237 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use super::{DeviceStatus, Register};
+use crate::DDR5_MAX_SIZE;
+
+///
+/// The size of an SPD5118 write protection block.
+///
+pub const PROTECTION_BLOCK_SIZE: usize = 64;
+
+///
+/// The write protection of the sixteen NVM blocks, as held in MR12 (blocks
+/// 0 to 7) and MR13 (blocks 8 to 15).  In-band, protection can only be set;
+/// clearing it requires the device to be in offline mode, with the HSA pin
+/// tied low.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BlockProtection(pub u16);
+
+impl BlockProtection {
+    pub const NONE: BlockProtection = BlockProtection(0);
+    pub const ALL: BlockProtection = BlockProtection(0xffff);
+
+    pub fn decode(mr12: u8, mr13: u8) -> Self {
+        Self(mr12 as u16 | (mr13 as u16) << 8)
+    }
+
+    ///
+    /// The blocks containing any of `len` bytes at the given offset, or
+    /// `None` if the range extends beyond the end of the NVM.
+    ///
+    pub fn covering(offset: usize, len: usize) -> Option<Self> {
+        let end = offset.checked_add(len)?;
+
+        if len == 0 || end > DDR5_MAX_SIZE {
+            return None;
+        }
+
+        let first = offset / PROTECTION_BLOCK_SIZE;
+        let last = (end - 1) / PROTECTION_BLOCK_SIZE;
+
+        Some(Self((first..=last).fold(0, |mask, b| mask | 1 << b)))
+    }
+
+    pub fn is_protected(&self, block: u8) -> bool {
+        block < 16 && self.0 & (1 << block) != 0
+    }
+
+    ///
+    /// The writes of this protection to MR12 and MR13.
+    ///
+    pub fn writes(&self) -> [[u8; 2]; 2] {
+        [
+            Register::WriteProtection0.write(self.0 as u8),
+            Register::WriteProtection1.write((self.0 >> 8) as u8),
+        ]
+    }
+
+    ///
+    /// The sequence that clears protection of `blocks` in offline mode,
+    /// leaving the protection of other blocks as it is.
+    ///
+    pub fn unprotect(&self, blocks: BlockProtection) -> OverrideSequence {
+        OverrideSequence::new(BlockProtection(self.0 & !blocks.0), true)
+    }
+
+    ///
+    /// The sequence that (re)protects `blocks`, in addition to those already
+    /// protected.  This does not require offline mode.
+    ///
+    pub fn reprotect(&self, blocks: BlockProtection) -> OverrideSequence {
+        OverrideSequence::new(BlockProtection(self.0 | blocks.0), false)
+    }
+}
+
+///
+/// Why a protection change cannot proceed, as determined from MR48.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OverrideError {
+    /// The device is not in offline mode, so protection cannot be cleared.
+    NotOffline,
+    /// A write to the NVM is still in progress.
+    Busy,
+}
+
+///
+/// A step of a protection change, to be issued in order to the hub's memory
+/// device code.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Read MR48 and pass it to [`OverrideSequence::check`], aborting the
+    /// sequence on error.
+    CheckStatus,
+    /// Write the two bytes (register and value).
+    Write([u8; 2]),
+    /// Poll MR48 until the NVM is no longer busy.
+    WaitIdle,
+    /// Read MR12 and MR13 and confirm that they decode to this protection.
+    Verify(BlockProtection),
+}
+
+///
+/// The exact register sequence for changing block protection:  check the
+/// device status, write MR12 and MR13, wait for the NVM to commit the new
+/// protection, then read it back.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OverrideSequence {
+    target: BlockProtection,
+    offline: bool,
+}
+
+impl OverrideSequence {
+    pub(crate) fn new(target: BlockProtection, offline: bool) -> Self {
+        Self { target, offline }
+    }
+
+    ///
+    /// The protection in effect once the sequence has completed.
+    ///
+    pub fn target(&self) -> BlockProtection {
+        self.target
+    }
+
+    ///
+    /// Returns true if the sequence requires offline mode.
+    ///
+    pub fn requires_offline(&self) -> bool {
+        self.offline
+    }
+
+    ///
+    /// Check the device status against the preconditions of the sequence.
+    ///
+    pub fn check(&self, status: DeviceStatus) -> Result<(), OverrideError> {
+        if self.offline && !status.write_protect_override {
+            Err(OverrideError::NotOffline)
+        } else if status.nvm_busy {
+            Err(OverrideError::Busy)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn steps(&self) -> [Step; 5] {
+        let [mr12, mr13] = self.target.writes();
+
+        [
+            Step::CheckStatus,
+            Step::Write(mr12),
+            Step::Write(mr13),
+            Step::WaitIdle,
+            Step::Verify(self.target),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Function, MockSpdDevice, SelectAddress, SpdBus};
+
+    #[test]
+    fn covering() {
+        assert_eq!(BlockProtection::covering(0, 64), Some(BlockProtection(1)));
+        assert_eq!(
+            BlockProtection::covering(0x3f, 2),
+            Some(BlockProtection(0b11))
+        );
+        assert_eq!(
+            BlockProtection::covering(0x200, 0x200),
+            Some(BlockProtection(0xff00))
+        );
+        assert_eq!(BlockProtection::covering(0x3ff, 2), None);
+        assert_eq!(BlockProtection::covering(0, 0), None);
+        assert_eq!(BlockProtection::covering(usize::MAX, 2), None);
+    }
+
+    #[test]
+    fn sequence() {
+        let sa = SelectAddress::new(0);
+        let code = Function::Memory(sa).to_device_code().unwrap();
+        let mut dev = MockSpdDevice::ddr5(sa, &[0; DDR5_MAX_SIZE]);
+
+        let run = |dev: &mut MockSpdDevice, seq: OverrideSequence| {
+            for step in seq.steps().iter() {
+                let mut buf = [0u8; 2];
+
+                match *step {
+                    Step::CheckStatus | Step::WaitIdle => {
+                        dev.write_read(code, &[48], &mut buf[..1]).unwrap();
+                        seq.check(DeviceStatus::decode(buf[0]))?;
+                    }
+                    Step::Write(bytes) => dev.write(code, &bytes).unwrap(),
+                    Step::Verify(target) => {
+                        dev.write_read(code, &[12], &mut buf).unwrap();
+                        assert_eq!(
+                            BlockProtection::decode(buf[0], buf[1]),
+                            target
+                        );
+                    }
+                }
+            }
+
+            Ok(())
+        };
+
+        let blocks = BlockProtection::covering(0x200, 0x80).unwrap();
+        let seq = BlockProtection::NONE.reprotect(blocks);
+        assert!(!seq.requires_offline());
+        assert_eq!(seq.target(), BlockProtection(0x0300));
+        assert_eq!(seq.steps()[2], Step::Write([13, 0x03]));
+        run(&mut dev, seq).unwrap();
+        assert!(dev.emulator().is_protected(9));
+
+        let seq = BlockProtection::ALL.unprotect(BlockProtection(0x0100));
+        assert_eq!(seq.target(), BlockProtection(0xfeff));
+
+        let seq = seq.target().unprotect(BlockProtection::ALL);
+        assert_eq!(run(&mut dev, seq), Err(OverrideError::NotOffline));
+
+        dev.emulator().set_offline(true);
+        run(&mut dev, BlockProtection(0x0300).unprotect(blocks)).unwrap();
+        assert!(!dev.emulator().is_protected(9));
+
+        assert_eq!(
+            seq.check(DeviceStatus::decode(0b1100)),
+            Err(OverrideError::Busy)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use super::Offset;
+use crate::Picoseconds;
+
+///
+/// DDR5 timing parameters.  Unlike DDR4, these are stored as 16-bit
+/// little-endian values with 1 ps granularity (or, for the refresh
+/// parameters, 1 ns granularity), with no fine timebase correction.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Timing {
+    TCkAvgMin,
+    TCkAvgMax,
+    TAAMin,
+    TRCDMin,
+    TRPMin,
+    TRASMin,
+    TRCMin,
+    TWRMin,
+    TRFC1Min,
+    TRFC2Min,
+    TRFCsbMin,
+    TRRDLMin,
+    TCCDLMin,
+    TCCDLWRMin,
+    TCCDLWR2Min,
+    TFAWMin,
+    TCCDLWTRMin,
+    TCCDSWTRMin,
+    TRTPMin,
+}
+
+impl Timing {
+    pub const ALL: &'static [Timing] = &[
+        Timing::TCkAvgMin,
+        Timing::TCkAvgMax,
+        Timing::TAAMin,
+        Timing::TRCDMin,
+        Timing::TRPMin,
+        Timing::TRASMin,
+        Timing::TRCMin,
+        Timing::TWRMin,
+        Timing::TRFC1Min,
+        Timing::TRFC2Min,
+        Timing::TRFCsbMin,
+        Timing::TRRDLMin,
+        Timing::TCCDLMin,
+        Timing::TCCDLWRMin,
+        Timing::TCCDLWR2Min,
+        Timing::TFAWMin,
+        Timing::TCCDLWTRMin,
+        Timing::TCCDSWTRMin,
+        Timing::TRTPMin,
+    ];
+
+    ///
+    /// The offset of the least significant byte of the parameter.
+    ///
+    pub fn offset(self) -> Offset {
+        match self {
+            Timing::TCkAvgMin => Offset::TCkAvgMinLSB,
+            Timing::TCkAvgMax => Offset::TCkAvgMaxLSB,
+            Timing::TAAMin => Offset::TAAMinLSB,
+            Timing::TRCDMin => Offset::TRCDMinLSB,
+            Timing::TRPMin => Offset::TRPMinLSB,
+            Timing::TRASMin => Offset::TRASMinLSB,
+            Timing::TRCMin => Offset::TRCMinLSB,
+            Timing::TWRMin => Offset::TWRMinLSB,
+            Timing::TRFC1Min => Offset::TRFC1MinLSB,
+            Timing::TRFC2Min => Offset::TRFC2MinLSB,
+            Timing::TRFCsbMin => Offset::TRFCsbMinLSB,
+            Timing::TRRDLMin => Offset::TRRDLMinLSB,
+            Timing::TCCDLMin => Offset::TCCDLMinLSB,
+            Timing::TCCDLWRMin => Offset::TCCDLWRMinLSB,
+            Timing::TCCDLWR2Min => Offset::TCCDLWR2MinLSB,
+            Timing::TFAWMin => Offset::TFAWMinLSB,
+            Timing::TCCDLWTRMin => Offset::TCCDLWTRMinLSB,
+            Timing::TCCDSWTRMin => Offset::TCCDSWTRMinLSB,
+            Timing::TRTPMin => Offset::TRTPMinLSB,
+        }
+    }
+
+    ///
+    /// The offset of the lower limit in clocks (nCK) that accompanies the
+    /// parameter, if any.
+    ///
+    pub fn nck_offset(self) -> Option<Offset> {
+        match self {
+            Timing::TRRDLMin => Some(Offset::TRRDLMinNCK),
+            Timing::TCCDLMin => Some(Offset::TCCDLMinNCK),
+            Timing::TCCDLWRMin => Some(Offset::TCCDLWRMinNCK),
+            Timing::TCCDLWR2Min => Some(Offset::TCCDLWR2MinNCK),
+            Timing::TFAWMin => Some(Offset::TFAWMinNCK),
+            Timing::TCCDLWTRMin => Some(Offset::TCCDLWTRMinNCK),
+            Timing::TCCDSWTRMin => Some(Offset::TCCDSWTRMinNCK),
+            Timing::TRTPMin => Some(Offset::TRTPMinNCK),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The unit of the stored value in picoseconds:  the refresh parameters
+    /// are stored in nanoseconds.
+    ///
+    fn unit(self) -> u32 {
+        match self {
+            Timing::TRFC1Min | Timing::TRFC2Min | Timing::TRFCsbMin => 1000,
+            _ => 1,
+        }
+    }
+
+    pub fn within(self, buf: &[u8]) -> Picoseconds {
+        Picoseconds(self.offset().within_u16(buf) as u32 * self.unit())
+    }
+
+    ///
+    /// The lower limit in clocks, if the parameter has one.
+    ///
+    pub fn nck_within(self, buf: &[u8]) -> Option<u8> {
+        self.nck_offset().map(|offset| offset.within(buf))
+    }
+
+    ///
+    /// Encode the given value into the image.  Returns `None` (leaving the
+    /// image untouched) if the value cannot be represented.
+    ///
+    pub fn set(self, buf: &mut [u8], ps: Picoseconds) -> Option<()> {
+        if !ps.0.is_multiple_of(self.unit())
+            || ps.0 / self.unit() > u16::MAX as u32
+        {
+            return None;
+        }
+
+        let offset = self.offset().to_usize();
+        let value = (ps.0 / self.unit()) as u16;
+        buf[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+        Some(())
+    }
+}
+
+///
+/// The correction applied when converting a timing to clock cycles, in
+/// thousandths:  DDR5 reduces each timing by 0.3% before rounding up.
+///
+const ROUNDING_CORRECTION: u64 = 997;
+
+///
+/// Convert a timing to a number of clock cycles at the given tCK, using the
+/// integer rounding algorithm of JESD400-5.  Saturates at `u16::MAX`
+/// (including for a zero tCK).
+///
+pub fn clocks(timing: Picoseconds, tck: Picoseconds) -> u16 {
+    if tck.0 == 0 {
+        return u16::MAX;
+    }
+
+    let nck =
+        (timing.0 as u64 * ROUNDING_CORRECTION / tck.0 as u64 + 1000) / 1000;
+
+    nck.min(u16::MAX as u64) as u16
+}
+
+///
+/// The CAS latencies supported by a DDR5 device.  Bit n of the five-byte
+/// bitmap indicates support for CL 20 + 2n.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CasLatencies(pub u64);
+
+impl CasLatencies {
+    pub const MIN: u8 = 20;
+    pub const MAX: u8 = 98;
+
+    pub fn from_spd(buf: &[u8]) -> Self {
+        let base = Offset::CASLatencies0.to_usize();
+
+        Self(
+            buf[base..=Offset::CASLatencies4.to_usize()]
+                .iter()
+                .rev()
+                .fold(0, |bits, &b| (bits << 8) | b as u64),
+        )
+    }
+
+    pub fn supports(&self, cl: u8) -> bool {
+        (Self::MIN..=Self::MAX).contains(&cl)
+            && cl.is_multiple_of(2)
+            && self.0 & (1 << ((cl - Self::MIN) / 2)) != 0
+    }
+
+    ///
+    /// Iterate over the supported CAS latencies in ascending order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (Self::MIN..=Self::MAX)
+            .step_by(2)
+            .filter(move |&cl| self.supports(cl))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings() {
+        let mut buf = [0u8; 1024];
+
+        //
+        // DDR5-4800B: tCKAVGmin of 416 ps, tAA of 16 ns, tRFC1 of 295 ns.
+        //
+        buf[0x14] = 0xa0;
+        buf[0x15] = 0x01;
+        buf[0x1e] = 0x80;
+        buf[0x1f] = 0x3e;
+        buf[0x2a] = 0x27;
+        buf[0x2b] = 0x01;
+        buf[0x52] = 0x40;
+        buf[0x53] = 0x36;
+        buf[0x54] = 0x20;
+
+        assert_eq!(Timing::TCkAvgMin.within(&buf), Picoseconds(416));
+        assert_eq!(Timing::TAAMin.within(&buf), Picoseconds(16000));
+        assert_eq!(Timing::TRFC1Min.within(&buf), Picoseconds(295_000));
+        assert_eq!(Timing::TFAWMin.within(&buf), Picoseconds(13888));
+        assert_eq!(Timing::TFAWMin.nck_within(&buf), Some(32));
+        assert_eq!(Timing::TAAMin.nck_within(&buf), None);
+
+        Timing::TRCDMin.set(&mut buf, Picoseconds(16000)).unwrap();
+        assert_eq!(Timing::TRCDMin.within(&buf), Picoseconds(16000));
+        assert_eq!(Timing::TRFC2Min.set(&mut buf, Picoseconds(1500)), None);
+        assert_eq!(Timing::TRPMin.set(&mut buf, Picoseconds(70000)), None);
+    }
+
+    #[test]
+    fn cas_latencies() {
+        let mut buf = [0u8; 1024];
+
+        //
+        // CL 22, 26 through 38, and 98.
+        //
+        buf[0x18] = 0b1111_1010;
+        buf[0x19] = 0b0000_0011;
+        buf[0x1c] = 0b1000_0000;
+
+        let cl = CasLatencies::from_spd(&buf);
+        assert!(cl.supports(22));
+        assert!(!cl.supports(20));
+        assert!(!cl.supports(23));
+        assert!(cl.supports(98));
+        assert!(!cl.supports(100));
+
+        let mut iter = cl.iter();
+        assert_eq!(iter.next(), Some(22));
+        assert_eq!(iter.next(), Some(26));
+        assert_eq!(cl.iter().count(), 9);
+        assert_eq!(cl.iter().last(), Some(98));
+    }
+
+    #[test]
+    fn clocks() {
+        let ps = Picoseconds;
+
+        //
+        // DDR5-4800 (416 ps):  tAA of 16 ns is 38.46 clocks, and a timing
+        // within 0.3% above a whole number of clocks rounds down.
+        //
+        assert_eq!(super::clocks(ps(16000), ps(416)), 39);
+        assert_eq!(super::clocks(ps(14976), ps(416)), 36);
+        assert_eq!(super::clocks(ps(15010), ps(416)), 36);
+        assert_eq!(super::clocks(ps(15060), ps(416)), 37);
+        assert_eq!(super::clocks(ps(1), ps(0)), u16::MAX);
+    }
+}
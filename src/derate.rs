@@ -0,0 +1,273 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{
+    clocks, ddr5, CasLatencies, DeviceType, Field, Picoseconds, Timing,
+    DDR5_MAX_SIZE, MAX_SIZE, MTB_PS,
+};
+
+///
+/// The largest values, in clocks, that a memory controller can program.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockLimits {
+    pub cl: u8,
+    pub trcd: u16,
+    pub trp: u16,
+    pub tras: u16,
+}
+
+///
+/// A timing parameter of an [`OperatingPoint`].
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Parameter {
+    Cl,
+    TRcd,
+    TRp,
+    TRas,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DerateError {
+    /// The image is not of a known device type, or is too short.
+    UnknownDeviceType,
+    /// The target tCK is shorter than the device's tCKmin.
+    TooFast,
+    /// The target tCK is longer than the device's tCKmax.
+    TooSlow,
+    /// No supported CAS latency satisfies tAA at the target tCK.
+    NoCasLatency,
+    /// The parameter exceeds the controller's limit at the target tCK.
+    Infeasible(Parameter),
+}
+
+///
+/// The timings of a device when run at a particular clock, in clocks.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OperatingPoint {
+    pub tck: Picoseconds,
+    pub cl: u8,
+    pub trcd: u16,
+    pub trp: u16,
+    pub tras: u16,
+}
+
+impl OperatingPoint {
+    ///
+    /// Derive the timings of the device described by `buf` when run with
+    /// the target tCK:  the CAS latency is the smallest supported one that
+    /// meets tAA, and every parameter must be within `limits`.
+    ///
+    pub fn derate(
+        buf: &[u8],
+        tck: Picoseconds,
+        limits: &ClockLimits,
+    ) -> Result<Self, DerateError> {
+        let device_type =
+            DeviceType::from_spd(buf).ok_or(DerateError::UnknownDeviceType)?;
+
+        //
+        // The timings are ordered tCKmin, tCKmax, tRCD, tRP and tRAS,
+        // and are converted to clocks with the device type's rounding.  The
+        // CAS latency is the smallest supported one that meets tAA.
+        //
+        let (ps, nck, cl): ([Picoseconds; 5], fn(_, _) -> u16, _) =
+            match device_type {
+                DeviceType::Ddr4 | DeviceType::Ddr4E
+                    if buf.len() >= MAX_SIZE =>
+                {
+                    let taa = clocks(Timing::TAAMin.within(buf), tck);
+                    let ps = [
+                        Timing::TCkAvgMin.within(buf),
+                        Timing::TCkAvgMax.within(buf),
+                        Timing::TRCDMin.within(buf),
+                        Timing::TRPMin.within(buf),
+                        Picoseconds(
+                            Field::TRAS_MIN.get_u16(buf).unwrap_or(0) as u32
+                                * MTB_PS,
+                        ),
+                    ];
+
+                    let cl = CasLatencies::from_spd(buf)
+                        .iter()
+                        .find(|&cl| cl as u16 >= taa);
+
+                    (ps, clocks, cl)
+                }
+                DeviceType::Ddr5 if buf.len() >= DDR5_MAX_SIZE => {
+                    let taa =
+                        ddr5::clocks(ddr5::Timing::TAAMin.within(buf), tck);
+                    let ps = [
+                        ddr5::Timing::TCkAvgMin.within(buf),
+                        ddr5::Timing::TCkAvgMax.within(buf),
+                        ddr5::Timing::TRCDMin.within(buf),
+                        ddr5::Timing::TRPMin.within(buf),
+                        ddr5::Timing::TRASMin.within(buf),
+                    ];
+
+                    let cl = ddr5::CasLatencies::from_spd(buf)
+                        .iter()
+                        .find(|&cl| cl as u16 >= taa);
+
+                    (ps, ddr5::clocks, cl)
+                }
+                _ => return Err(DerateError::UnknownDeviceType),
+            };
+
+        let [tck_min, tck_max, trcd, trp, tras] = ps;
+
+        if tck.0 == 0 || tck < tck_min {
+            return Err(DerateError::TooFast);
+        }
+
+        if tck_max.0 != 0 && tck > tck_max {
+            return Err(DerateError::TooSlow);
+        }
+
+        let cl = cl.ok_or(DerateError::NoCasLatency)?;
+
+        let point = Self {
+            tck,
+            cl,
+            trcd: nck(trcd, tck),
+            trp: nck(trp, tck),
+            tras: nck(tras, tck),
+        };
+
+        if point.cl > limits.cl {
+            Err(DerateError::Infeasible(Parameter::Cl))
+        } else if point.trcd > limits.trcd {
+            Err(DerateError::Infeasible(Parameter::TRcd))
+        } else if point.trp > limits.trp {
+            Err(DerateError::Infeasible(Parameter::TRp))
+        } else if point.tras > limits.tras {
+            Err(DerateError::Infeasible(Parameter::TRas))
+        } else {
+            Ok(point)
+        }
+    }
+
+    ///
+    /// The data rate, in MT/s.
+    ///
+    pub fn data_rate(&self) -> u32 {
+        2_000_000 / self.tck.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: ClockLimits = ClockLimits {
+        cl: 32,
+        trcd: 32,
+        trp: 32,
+        tras: 64,
+    };
+
+    #[test]
+    fn ddr4() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+
+        //
+        // DDR4-3200 22-22-22 (13.75 ns), supporting CL 10 through 24, with
+        // a tRAS of 32 ns.
+        //
+        Timing::TCkAvgMin.set(&mut buf, Picoseconds(625)).unwrap();
+        Timing::TCkAvgMax.set(&mut buf, Picoseconds(1600)).unwrap();
+        for t in [Timing::TAAMin, Timing::TRCDMin, Timing::TRPMin].iter() {
+            t.set(&mut buf, Picoseconds(13750)).unwrap();
+        }
+        Field::TRAS_MIN.set_u16(&mut buf, 256).unwrap();
+        buf[0x14..0x17].copy_from_slice(&[0xf8, 0xff, 0x03]);
+
+        let derate = |buf: &[u8], ps| {
+            OperatingPoint::derate(buf, Picoseconds(ps), &LIMITS)
+        };
+
+        let point = derate(&buf, 625).unwrap();
+        assert_eq!((point.cl, point.trcd, point.tras), (22, 22, 52));
+        assert_eq!(point.data_rate(), 3200);
+
+        //
+        // At DDR4-2666, tAA is 18.33 clocks:  CL 19.
+        //
+        let point = derate(&buf, 750).unwrap();
+        assert_eq!((point.cl, point.trcd, point.trp), (19, 19, 19));
+        assert_eq!(point.tras, 43);
+
+        assert_eq!(derate(&buf, 600), Err(DerateError::TooFast));
+        assert_eq!(derate(&buf, 1700), Err(DerateError::TooSlow));
+
+        //
+        // Without CL 22 and above, DDR4-3200 is unreachable.
+        //
+        buf[0x16] = 0x00;
+        buf[0x15] = 0x7f;
+        assert_eq!(derate(&buf, 625), Err(DerateError::NoCasLatency));
+
+        assert_eq!(derate(&buf[..4], 625), Err(DerateError::UnknownDeviceType));
+    }
+
+    #[test]
+    fn ddr5() {
+        //
+        // DDR5-4800 with 16 ns tAA, tRCD and tRP and 32 ns tRAS, supporting
+        // CL 22 through 40.
+        //
+        let mut builder = ddr5::Builder::new(0x01)
+            .cas_latencies(ddr5::CasLatencies(0x7fe))
+            .timing(ddr5::Timing::TCkAvgMin, Picoseconds(416))
+            .timing(ddr5::Timing::TRASMin, Picoseconds(32000));
+
+        for t in [
+            ddr5::Timing::TAAMin,
+            ddr5::Timing::TRCDMin,
+            ddr5::Timing::TRPMin,
+        ]
+        .iter()
+        {
+            builder = builder.timing(*t, Picoseconds(16000));
+        }
+
+        let buf = builder.build().unwrap();
+
+        //
+        // tAA is 38.35 clocks; CL 39 is not supported, so CL 40 is used.
+        // This exceeds the controller's limits, as does tRAS at 77 clocks.
+        //
+        let derate =
+            |limits| OperatingPoint::derate(&buf, Picoseconds(416), &limits);
+
+        assert_eq!(derate(LIMITS), Err(DerateError::Infeasible(Parameter::Cl)));
+
+        let limits = ClockLimits {
+            cl: 40,
+            trcd: 40,
+            trp: 40,
+            ..LIMITS
+        };
+        assert_eq!(
+            derate(limits),
+            Err(DerateError::Infeasible(Parameter::TRas))
+        );
+
+        let limits = ClockLimits { tras: 80, ..limits };
+        let point = derate(limits).unwrap();
+        assert_eq!((point.cl, point.trcd, point.tras), (40, 39, 77));
+
+        //
+        // At DDR5-4000, tAA is exactly 32 clocks.
+        //
+        let point =
+            OperatingPoint::derate(&buf, Picoseconds(500), &LIMITS).unwrap();
+        assert_eq!((point.cl, point.trcd, point.tras), (32, 32, 64));
+    }
+}
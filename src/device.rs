@@ -0,0 +1,273 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{
+    execute, Delay, Ee1004Pages, Function, Operation, PageSelector, Planner,
+    SelectAddress, Spd5118Pages, SpdBus, Summary, DDR5_MAX_SIZE,
+    WRITE_CYCLE_MS,
+};
+
+///
+/// Byte-addressed access to an SPD, independent of DDR generation and of
+/// how the device is reached.  Decoders, validators and inventory code can
+/// be written once against this trait.
+///
+pub trait SpdDevice {
+    type Error;
+
+    ///
+    /// The size of the image, in bytes.
+    ///
+    fn size(&self) -> usize;
+
+    fn read_at(
+        &mut self,
+        offset: usize,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
+    fn write_at(
+        &mut self,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    ///
+    /// Read the whole image and summarize it.  Returns `Ok(None)` if the
+    /// image cannot be decoded.
+    ///
+    fn identity(&mut self) -> Result<Option<Summary>, Self::Error> {
+        let mut buf = [0u8; DDR5_MAX_SIZE];
+        let len = self.size().min(DDR5_MAX_SIZE);
+
+        self.read_at(0, &mut buf[..len])?;
+        Ok(Summary::from_spd(&buf[..len]))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DeviceError<E> {
+    /// The bus transaction failed.
+    Bus(E),
+    /// The access extends beyond the end of the image.
+    OutOfRange,
+}
+
+///
+/// Issue operations one at a time, waiting out the write cycle time after
+/// each write:  until a written page has been committed, the device NACKs
+/// everything, including the write of the next page.  Returns the number of
+/// writes issued.
+///
+pub(crate) fn execute_writes<B: SpdBus>(
+    bus: &mut B,
+    ops: impl Iterator<Item = Operation>,
+    delay: &mut impl Delay,
+) -> Result<usize, B::Error> {
+    let mut writes = 0;
+
+    for op in ops {
+        let write = matches!(op, Operation::Write { .. });
+        execute(bus, core::iter::once(op), &mut [])?;
+
+        if write {
+            writes += 1;
+            delay.delay_ms(WRITE_CYCLE_MS);
+        }
+    }
+
+    Ok(writes)
+}
+
+///
+/// An [`SpdDevice`] reached over an [`SpdBus`], with pages selected as the
+/// [`PageSelector`] dictates:  EE1004 for DDR4, SPD5118 for DDR5.  Writes
+/// wait out the write cycle time ([`WRITE_CYCLE_MS`]) after each write page
+/// using the given [`Delay`].
+///
+pub struct PagedDevice<B, P, D> {
+    bus: B,
+    size: usize,
+    planner: Planner<P>,
+    delay: D,
+}
+
+impl<B: SpdBus, D: Delay> PagedDevice<B, Ee1004Pages, D> {
+    pub fn ee1004(bus: B, sa: SelectAddress, delay: D) -> Self {
+        Self::new(bus, Ee1004Pages, sa, delay)
+    }
+}
+
+impl<B: SpdBus, D: Delay> PagedDevice<B, Spd5118Pages, D> {
+    pub fn spd5118(bus: B, sa: SelectAddress, delay: D) -> Self {
+        Self::new(bus, Spd5118Pages(sa), sa, delay)
+    }
+}
+
+impl<B: SpdBus, P: PageSelector, D: Delay> PagedDevice<B, P, D> {
+    pub fn new(bus: B, pages: P, sa: SelectAddress, delay: D) -> Self {
+        let code = Function::Memory(sa).to_device_code().unwrap();
+
+        Self {
+            bus,
+            size: pages.size(),
+            planner: Planner::new(pages, code),
+            delay,
+        }
+    }
+
+    pub fn bus(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    ///
+    /// Forget the selected page, as must be done if another agent may have
+    /// changed it.
+    ///
+    pub fn invalidate(&mut self) {
+        self.planner.invalidate();
+    }
+
+    pub fn into_inner(self) -> B {
+        self.bus
+    }
+
+    pub(crate) fn parts(&mut self) -> (&mut B, &mut Planner<P>, &mut D) {
+        (&mut self.bus, &mut self.planner, &mut self.delay)
+    }
+
+    fn check(
+        &self,
+        offset: usize,
+        len: usize,
+    ) -> Result<(), DeviceError<B::Error>> {
+        if offset.checked_add(len).is_some_and(|end| end <= self.size) {
+            Ok(())
+        } else {
+            Err(DeviceError::OutOfRange)
+        }
+    }
+
+    ///
+    /// On a bus error, the selected page is no longer known.
+    ///
+    fn finish(
+        &mut self,
+        result: Result<usize, B::Error>,
+    ) -> Result<(), DeviceError<B::Error>> {
+        result.map(|_| ()).map_err(|err| {
+            self.planner.invalidate();
+            DeviceError::Bus(err)
+        })
+    }
+}
+
+impl<B: SpdBus, P: PageSelector, D: Delay> SpdDevice for PagedDevice<B, P, D> {
+    type Error = DeviceError<B::Error>;
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn read_at(
+        &mut self,
+        offset: usize,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.check(offset, buf.len())?;
+
+        let plan = self.planner.read(offset, buf.len());
+        let result = execute(&mut self.bus, plan, buf);
+        self.finish(result)
+    }
+
+    fn write_at(
+        &mut self,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.check(offset, data.len())?;
+
+        let plan = self.planner.write(offset, data);
+        let result = execute_writes(&mut self.bus, plan, &mut self.delay);
+        self.finish(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ddr5, DeviceType, MockSpdDevice, Nack, MAX_SIZE};
+    use core::cell::Cell;
+
+    ///
+    /// Generation-agnostic code, as a decoder or inventory would be.
+    ///
+    fn serial<D: SpdDevice>(dev: &mut D) -> Result<Option<u32>, D::Error> {
+        Ok(dev.identity()?.map(|s| s.serial))
+    }
+
+    #[test]
+    fn ddr4() {
+        let sa = SelectAddress::new(2);
+        let mut image = [0u8; MAX_SIZE];
+        image[0x002] = 0x0c;
+        image[0x145..0x149].copy_from_slice(&[0, 0, 0x12, 0x34]);
+
+        let delays = Cell::new(0);
+        let delay = |ms| {
+            assert_eq!(ms, WRITE_CYCLE_MS);
+            delays.set(delays.get() + 1);
+        };
+
+        let mut dev =
+            PagedDevice::ee1004(MockSpdDevice::ddr4(sa, &image), sa, delay);
+        assert_eq!(serial(&mut dev), Ok(Some(0x1234)));
+
+        //
+        // A write crossing a write page (and here, a page) boundary waits
+        // for each page to be committed.
+        //
+        dev.write_at(0x0ff, &[0xaa, 0xbb]).unwrap();
+        assert_eq!(delays.get(), 2);
+
+        let mut buf = [0u8; 2];
+        dev.read_at(0x0ff, &mut buf).unwrap();
+        assert_eq!(buf, [0xaa, 0xbb]);
+        assert_eq!(delays.get(), 2);
+
+        assert_eq!(dev.read_at(0x1ff, &mut buf), Err(DeviceError::OutOfRange));
+        assert_eq!(
+            dev.write_at(usize::MAX, &buf),
+            Err(DeviceError::OutOfRange)
+        );
+
+        let wrong = SelectAddress::new(3);
+        let mut dev =
+            PagedDevice::ee1004(MockSpdDevice::ddr4(sa, &image), wrong, delay);
+        assert_eq!(
+            dev.read_at(0, &mut buf),
+            Err(DeviceError::Bus(Nack { code: 0x53 }))
+        );
+    }
+
+    #[test]
+    fn ddr5() {
+        let sa = SelectAddress::new(0);
+        let image = ddr5::Builder::new(0x01).serial(0x5678).build().unwrap();
+        let mut dev =
+            PagedDevice::spd5118(MockSpdDevice::ddr5(sa, &image), sa, |_| {});
+
+        assert_eq!(dev.size(), DDR5_MAX_SIZE);
+        assert_eq!(serial(&mut dev), Ok(Some(0x5678)));
+
+        let summary = dev.identity().unwrap().unwrap();
+        assert_eq!(summary.device_type, DeviceType::Ddr5);
+
+        dev.write_at(0x27f, &[1, 2]).unwrap();
+        assert_eq!(dev.into_inner().image()[0x27f..0x281], [1, 2]);
+    }
+}
@@ -0,0 +1,250 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::device::execute_writes;
+use crate::{
+    validate_spd, DeviceError, DeviceType, PageSelector, PagedDevice, SpdBus,
+    SpdDevice, SpdImage, ValidationError, DDR5_MAX_SIZE,
+};
+
+///
+/// The write cycle time (tWR) of both EE1004 and SPD5118 devices:  after a
+/// write, the device does not respond until the data has been committed.
+///
+pub const WRITE_CYCLE_MS: u32 = 5;
+
+///
+/// A means of waiting, as provided by the platform.
+///
+pub trait Delay {
+    fn delay_ms(&mut self, ms: u32);
+}
+
+impl<F: FnMut(u32)> Delay for F {
+    fn delay_ms(&mut self, ms: u32) {
+        self(ms)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TransferError<E> {
+    /// A bus transaction failed (for example, because a write was NACK'd by
+    /// a protected block).
+    Bus(E),
+    /// The buffer is smaller than the device.
+    BufferTooSmall,
+    /// The image to be restored is not valid for the device.
+    Invalid(ValidationError),
+    /// Read-back verification found a byte that was not written.
+    Mismatch {
+        offset: usize,
+        expected: u8,
+        actual: u8,
+    },
+}
+
+impl<E> From<DeviceError<E>> for TransferError<E> {
+    fn from(err: DeviceError<E>) -> Self {
+        match err {
+            DeviceError::Bus(err) => TransferError::Bus(err),
+            DeviceError::OutOfRange => TransferError::BufferTooSmall,
+        }
+    }
+}
+
+///
+/// The outcome of a successful dump or restore.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TransferReport {
+    /// The number of bytes read or restored.
+    pub len: usize,
+    /// The number of write pages written; only those that differed from
+    /// the device's contents are written.
+    pub pages_written: usize,
+    pub device_type: Option<DeviceType>,
+    /// Whether the image passes validation, including its CRCs.
+    pub valid: bool,
+}
+
+///
+/// Read the entire contents of a device into the start of `buf`.
+///
+pub fn dump<B: SpdBus, P: PageSelector, D: Delay>(
+    device: &mut PagedDevice<B, P, D>,
+    buf: &mut [u8],
+) -> Result<TransferReport, TransferError<B::Error>> {
+    let len = device.size();
+    let buf = buf.get_mut(..len).ok_or(TransferError::BufferTooSmall)?;
+
+    device.read_at(0, buf)?;
+
+    Ok(TransferReport {
+        len,
+        pages_written: 0,
+        device_type: DeviceType::from_spd(buf),
+        valid: validate_spd(buf).is_ok(),
+    })
+}
+
+///
+/// Write an image to a device, waiting out the write cycle time after each
+/// write with the device's [`Delay`], then read it back to verify it.  The
+/// image must be valid (with correct CRCs) and exactly the size of the
+/// device; write pages that already hold the right contents are not
+/// rewritten.
+///
+pub fn restore<B: SpdBus, P: PageSelector, D: Delay>(
+    device: &mut PagedDevice<B, P, D>,
+    image: &[u8],
+) -> Result<TransferReport, TransferError<B::Error>> {
+    validate_spd(image).map_err(TransferError::Invalid)?;
+
+    let len = device.size();
+
+    if image.len() != len {
+        return Err(TransferError::Invalid(ValidationError::Length));
+    }
+
+    let mut buf = [0u8; DDR5_MAX_SIZE];
+    device.read_at(0, &mut buf[..len])?;
+
+    let mut current = SpdImage::new(buf);
+    current.write(0usize, image).unwrap();
+
+    let result = {
+        let (bus, planner, delay) = device.parts();
+        execute_writes(bus, current.flush_plan(planner), delay)
+    };
+
+    let pages_written = match result {
+        Ok(writes) => writes,
+        Err(err) => {
+            device.invalidate();
+            return Err(TransferError::Bus(err));
+        }
+    };
+
+    device.read_at(0, &mut buf[..len])?;
+
+    if let Some(offset) = (0..len).find(|&i| buf[i] != image[i]) {
+        return Err(TransferError::Mismatch {
+            offset,
+            expected: image[offset],
+            actual: buf[offset],
+        });
+    }
+
+    Ok(TransferReport {
+        len,
+        pages_written,
+        device_type: DeviceType::from_spd(image),
+        valid: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ddr5, update_crc, Function, MockSpdDevice, Nack, SelectAddress,
+        MAX_SIZE,
+    };
+    use core::cell::Cell;
+
+    fn ddr4() -> [u8; MAX_SIZE] {
+        let mut image = [0u8; MAX_SIZE];
+        image[0x002] = 0x0c;
+        image[0x149..0x14d].copy_from_slice(b"TEST");
+        update_crc(&mut image);
+        image
+    }
+
+    #[test]
+    fn ddr4_round_trip() {
+        let sa = SelectAddress::new(1);
+        let original = ddr4();
+        let delays = Cell::new(0);
+        let delay = |ms| {
+            assert_eq!(ms, WRITE_CYCLE_MS);
+            delays.set(delays.get() + 1);
+        };
+        let mut dev =
+            PagedDevice::ee1004(MockSpdDevice::ddr4(sa, &original), sa, delay);
+
+        let mut buf = [0u8; MAX_SIZE];
+        let report = dump(&mut dev, &mut buf).unwrap();
+        assert_eq!(buf, original);
+        assert_eq!(report.device_type, Some(DeviceType::Ddr4));
+        assert!(report.valid);
+        assert_eq!(
+            dump(&mut dev, &mut buf[..256]),
+            Err(TransferError::BufferTooSmall)
+        );
+
+        //
+        // Changing the module type and the part number touches their write
+        // pages and that holding the CRC of the base configuration.
+        //
+        let mut image = original;
+        image[0x003] = 0x02;
+        image[0x149..0x14d].copy_from_slice(b"REWK");
+        update_crc(&mut image);
+
+        let report = restore(&mut dev, &image).unwrap();
+        assert_eq!(report.pages_written, 3);
+        assert_eq!(delays.get(), 3);
+        assert_eq!(dev.bus().image(), &image[..]);
+
+        assert_eq!(restore(&mut dev, &image).unwrap().pages_written, 0);
+        assert_eq!(delays.get(), 3);
+
+        image[0x003] = 0x03;
+        assert_eq!(
+            restore(&mut dev, &image),
+            Err(TransferError::Invalid(ValidationError::Crc))
+        );
+        assert_eq!(delays.get(), 3);
+
+        //
+        // A protected block NACKs the write.
+        //
+        update_crc(&mut image);
+        let swp0 = Function::ProtectionStatus(0).to_device_code().unwrap();
+        dev.bus().write(swp0, &[0, 0]).unwrap();
+
+        let code = Function::Memory(sa).to_device_code().unwrap();
+        assert_eq!(
+            restore(&mut dev, &image),
+            Err(TransferError::Bus(Nack { code }))
+        );
+    }
+
+    #[test]
+    fn ddr5_round_trip() {
+        let sa = SelectAddress::new(0);
+        let original = ddr5::Builder::new(0x01).serial(1).build().unwrap();
+        let image = ddr5::Builder::new(0x01).serial(2).build().unwrap();
+
+        let mut dev = PagedDevice::spd5118(
+            MockSpdDevice::ddr5(sa, &original),
+            sa,
+            |_| {},
+        );
+        assert_eq!(
+            restore(&mut dev, &image[..MAX_SIZE]),
+            Err(TransferError::Invalid(ValidationError::Length))
+        );
+
+        let report = restore(&mut dev, &image).unwrap();
+        assert_eq!(report.device_type, Some(DeviceType::Ddr5));
+        assert_eq!(report.pages_written, 1);
+
+        let mut buf = [0u8; DDR5_MAX_SIZE];
+        assert!(dump(&mut dev, &mut buf).unwrap().valid);
+        assert_eq!(buf, image);
+    }
+}
@@ -0,0 +1,420 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{Function, Page, SelectAddress, BLOCK_SIZE, MAX_SIZE};
+
+///
+/// Size of an SPD5118 (DDR5) image.
+///
+pub const DDR5_MAX_SIZE: usize = 1024;
+
+///
+/// Size of an SPD5118 NVM page, as selected by MR11.
+///
+pub const DDR5_PAGE_SIZE: usize = 128;
+
+///
+/// The size of the internal write buffer; writes roll over within an aligned
+/// chunk of this size.
+///
+pub const WRITE_PAGE_SIZE: usize = 16;
+
+const SPD5118_BLOCK_SIZE: usize = 64;
+
+///
+/// The SPD5118 registers that the emulator assigns meaning to.
+///
+const MR0: usize = 0;
+const MR1: usize = 1;
+const MR11: usize = 11;
+const MR12: usize = 12;
+const MR13: usize = 13;
+const MR48: usize = 48;
+
+///
+/// MR48 bit indicating that write protection may be overridden.
+///
+const WP_OVERRIDE: u8 = 1 << 2;
+
+///
+/// The device-side protocol being emulated.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Protocol {
+    /// EE1004 as found on DDR4 DIMMs: two 256-byte pages selected by the
+    /// SPA0/SPA1 broadcast commands, with four 128-byte protection blocks.
+    Ee1004,
+
+    /// SPD5118 as found on DDR5 DIMMs: eight 128-byte pages selected by
+    /// MR11, with sixteen 64-byte protection blocks controlled by MR12/MR13.
+    Spd5118,
+}
+
+///
+/// An emulation of the target side of an SPD EEPROM, suitable for wiring to
+/// an I2C peripheral running in target mode.  The peripheral is expected to
+/// call [`SpdEmulator::on_write`] and [`SpdEmulator::on_read`] with the device
+/// code that was addressed; the return value indicates whether the
+/// transaction should be ACK'd.
+///
+pub struct SpdEmulator {
+    protocol: Protocol,
+    select_address: SelectAddress,
+    image: [u8; DDR5_MAX_SIZE],
+    registers: [u8; 128],
+    page: u8,
+    address: u8,
+    protected: u16,
+    offline: bool,
+}
+
+impl SpdEmulator {
+    ///
+    /// Create an EE1004 emulator at the given select address.
+    ///
+    pub fn ee1004(
+        select_address: SelectAddress,
+        image: &[u8; MAX_SIZE],
+    ) -> Self {
+        let mut emulator = Self::new(Protocol::Ee1004, select_address);
+        emulator.image[..MAX_SIZE].copy_from_slice(image);
+        emulator
+    }
+
+    ///
+    /// Create an SPD5118 emulator at the given select address (HID).
+    ///
+    pub fn spd5118(
+        select_address: SelectAddress,
+        image: &[u8; DDR5_MAX_SIZE],
+    ) -> Self {
+        let mut emulator = Self::new(Protocol::Spd5118, select_address);
+        emulator.image.copy_from_slice(image);
+        emulator.registers[MR0] = 0x51;
+        emulator.registers[MR1] = 0x18;
+        emulator
+    }
+
+    fn new(protocol: Protocol, select_address: SelectAddress) -> Self {
+        Self {
+            protocol,
+            select_address,
+            image: [0; DDR5_MAX_SIZE],
+            registers: [0; 128],
+            page: 0,
+            address: 0,
+            protected: 0,
+            offline: false,
+        }
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub fn image(&self) -> &[u8] {
+        match self.protocol {
+            Protocol::Ee1004 => &self.image[..MAX_SIZE],
+            Protocol::Spd5118 => &self.image[..],
+        }
+    }
+
+    pub fn page(&self) -> u8 {
+        self.page
+    }
+
+    pub fn is_protected(&self, block: u8) -> bool {
+        block < self.nblocks() && self.protected & (1 << block) != 0
+    }
+
+    ///
+    /// On SPD5118, protection can only be cleared when the device is in
+    /// offline mode (HSA pin tied low), which is reflected in MR48.
+    ///
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+
+        if offline {
+            self.registers[MR48] |= WP_OVERRIDE;
+        } else {
+            self.registers[MR48] &= !WP_OVERRIDE;
+        }
+    }
+
+    fn nblocks(&self) -> u8 {
+        match self.protocol {
+            Protocol::Ee1004 => (MAX_SIZE / BLOCK_SIZE) as u8,
+            Protocol::Spd5118 => (DDR5_MAX_SIZE / SPD5118_BLOCK_SIZE) as u8,
+        }
+    }
+
+    fn block_of(&self, offset: usize) -> u8 {
+        match self.protocol {
+            Protocol::Ee1004 => (offset / BLOCK_SIZE) as u8,
+            Protocol::Spd5118 => (offset / SPD5118_BLOCK_SIZE) as u8,
+        }
+    }
+
+    ///
+    /// The absolute offset of the current address pointer, for NVM accesses.
+    ///
+    fn nvm_offset(&self, address: u8) -> usize {
+        match self.protocol {
+            Protocol::Ee1004 => Page(self.page).offset() + address as usize,
+            Protocol::Spd5118 => {
+                self.page as usize * DDR5_PAGE_SIZE + (address & 0x7f) as usize
+            }
+        }
+    }
+
+    ///
+    /// Advance the address pointer for a read, rolling over within the
+    /// current page.
+    ///
+    fn advance(&self, address: u8) -> u8 {
+        match self.protocol {
+            Protocol::Ee1004 => address.wrapping_add(1),
+            Protocol::Spd5118 => {
+                (address & 0x80) | (address.wrapping_add(1) & 0x7f)
+            }
+        }
+    }
+
+    ///
+    /// Advance the address pointer for a write, rolling over within the
+    /// current write page.
+    ///
+    fn advance_write(&self, address: u8) -> u8 {
+        let mask = (WRITE_PAGE_SIZE - 1) as u8;
+        (address & !mask) | (address.wrapping_add(1) & mask)
+    }
+
+    ///
+    /// Handle a write transaction to the given device code.  The first byte
+    /// of `bytes` (if any) is the address; the remainder is data.
+    ///
+    pub fn on_write(&mut self, code: u8, bytes: &[u8]) -> bool {
+        match (self.protocol, Function::from_device_code(code)) {
+            (_, Some(Function::Memory(sa))) if sa == self.select_address => {
+                match bytes.split_first() {
+                    None => true,
+                    Some((&address, data)) => {
+                        self.address = address;
+                        match self.protocol {
+                            Protocol::Ee1004 => self.write_nvm(data),
+                            Protocol::Spd5118 if address & 0x80 != 0 => {
+                                self.write_nvm(data)
+                            }
+                            Protocol::Spd5118 => self.write_registers(data),
+                        }
+                    }
+                }
+            }
+
+            (Protocol::Ee1004, Some(Function::ProtectionStatus(block))) => {
+                self.protected |= 1 << block;
+                true
+            }
+
+            (Protocol::Ee1004, Some(Function::ClearAllWriteProtection)) => {
+                self.protected = 0;
+                true
+            }
+
+            (Protocol::Ee1004, Some(Function::PageAddress(page))) => {
+                self.page = page.0;
+                true
+            }
+
+            _ => false,
+        }
+    }
+
+    fn write_nvm(&mut self, data: &[u8]) -> bool {
+        let mut ack = true;
+
+        for &byte in data {
+            let offset = self.nvm_offset(self.address);
+
+            if self.is_protected(self.block_of(offset)) {
+                ack = false;
+            } else {
+                self.image[offset] = byte;
+            }
+
+            self.address = self.advance_write(self.address);
+        }
+
+        ack
+    }
+
+    fn write_registers(&mut self, data: &[u8]) -> bool {
+        for &byte in data {
+            let reg = (self.address & 0x7f) as usize;
+
+            match reg {
+                MR11 => {
+                    self.registers[reg] = byte;
+                    self.page = byte & 0b111;
+                }
+
+                MR12 | MR13 => {
+                    let shift = if reg == MR12 { 0 } else { 8 };
+                    let current = (self.protected >> shift) as u8;
+
+                    let next = if self.offline { byte } else { current | byte };
+
+                    self.protected &= !(0xff << shift);
+                    self.protected |= (next as u16) << shift;
+                    self.registers[reg] = next;
+                }
+
+                _ => {}
+            }
+
+            self.address = self.advance(self.address);
+        }
+
+        true
+    }
+
+    ///
+    /// Handle a read transaction from the given device code, filling `buf`
+    /// from the current address.  For the EE1004 protection and page status
+    /// commands, the data is meaningless and the status is conveyed by the
+    /// returned ACK/NACK.
+    ///
+    pub fn on_read(&mut self, code: u8, buf: &mut [u8]) -> bool {
+        match (self.protocol, Function::from_device_code(code)) {
+            (_, Some(Function::Memory(sa))) if sa == self.select_address => {
+                for byte in buf.iter_mut() {
+                    *byte = match self.protocol {
+                        Protocol::Spd5118 if self.address & 0x80 == 0 => {
+                            self.registers[self.address as usize]
+                        }
+                        _ => self.image[self.nvm_offset(self.address)],
+                    };
+
+                    self.address = self.advance(self.address);
+                }
+
+                true
+            }
+
+            //
+            // RPSn: ACK if the block is not protected.
+            //
+            (Protocol::Ee1004, Some(Function::ProtectionStatus(block))) => {
+                buf.iter_mut().for_each(|b| *b = 0xff);
+                !self.is_protected(block)
+            }
+
+            //
+            // RPA: ACK if page 0 is selected.
+            //
+            (Protocol::Ee1004, Some(Function::PageAddress(Page(0)))) => {
+                buf.iter_mut().for_each(|b| *b = 0xff);
+                self.page == 0
+            }
+
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SA0: SelectAddress = SelectAddress::new(0);
+
+    #[test]
+    fn ee1004() {
+        let mut image = [0u8; MAX_SIZE];
+        image[0x140] = 0x80;
+        image[0x141] = 0xce;
+
+        let mut emul = SpdEmulator::ee1004(SA0, &image);
+        let mem = Function::Memory(SA0).to_device_code().unwrap();
+        let mut buf = [0u8; 2];
+
+        assert!(!emul.on_write(
+            Function::Memory(SelectAddress::new(1))
+                .to_device_code()
+                .unwrap(),
+            &[0]
+        ));
+
+        let spa1 = Function::PageAddress(Page(1)).to_device_code().unwrap();
+        let rpa = Function::PageAddress(Page(0)).to_device_code().unwrap();
+        assert!(emul.on_read(rpa, &mut buf));
+        assert!(emul.on_write(spa1, &[0, 0]));
+        assert!(!emul.on_read(rpa, &mut buf));
+
+        assert!(emul.on_write(mem, &[0x40]));
+        assert!(emul.on_read(mem, &mut buf));
+        assert_eq!(buf, [0x80, 0xce]);
+
+        assert!(emul.on_write(mem, &[0x80, 0xaa, 0xbb]));
+        assert_eq!(&emul.image()[0x180..0x182], &[0xaa, 0xbb]);
+
+        let swp3 = Function::ProtectionStatus(3).to_device_code().unwrap();
+        assert!(emul.on_read(swp3, &mut buf));
+        assert!(emul.on_write(swp3, &[0, 0]));
+        assert!(!emul.on_read(swp3, &mut buf));
+        assert!(!emul.on_write(mem, &[0x80, 0x11]));
+        assert_eq!(emul.image()[0x180], 0xaa);
+
+        let cwp = Function::ClearAllWriteProtection.to_device_code().unwrap();
+        assert!(emul.on_write(cwp, &[0, 0]));
+        assert!(emul.on_write(mem, &[0x80, 0x11]));
+        assert_eq!(emul.image()[0x180], 0x11);
+    }
+
+    #[test]
+    fn write_rollover() {
+        let mut emul = SpdEmulator::ee1004(SA0, &[0; MAX_SIZE]);
+        let mem = Function::Memory(SA0).to_device_code().unwrap();
+
+        assert!(emul.on_write(mem, &[0x0e, 1, 2, 3]));
+        assert_eq!(
+            &emul.image()[0..0x10],
+            &[3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn spd5118() {
+        let mut image = [0u8; DDR5_MAX_SIZE];
+        image[0x200] = 0x5a;
+
+        let mut emul = SpdEmulator::spd5118(SA0, &image);
+        let mem = Function::Memory(SA0).to_device_code().unwrap();
+        let mut buf = [0u8; 2];
+
+        assert!(emul.on_write(mem, &[0]));
+        assert!(emul.on_read(mem, &mut buf));
+        assert_eq!(buf, [0x51, 0x18]);
+
+        assert!(emul.on_write(mem, &[MR11 as u8, 4]));
+        assert_eq!(emul.page(), 4);
+        assert!(emul.on_write(mem, &[0x80]));
+        assert!(emul.on_read(mem, &mut buf));
+        assert_eq!(buf[0], 0x5a);
+
+        assert!(emul.on_write(mem, &[MR12 as u8, 0]));
+        assert!(emul.on_write(mem, &[MR13 as u8, 0b1]));
+        assert!(emul.is_protected(8));
+        assert!(!emul.on_write(mem, &[0x80, 0x00]));
+        assert_eq!(emul.image()[0x200], 0x5a);
+
+        assert!(emul.on_write(mem, &[MR13 as u8, 0]));
+        assert!(emul.is_protected(8));
+        emul.set_offline(true);
+        assert!(emul.on_write(mem, &[MR13 as u8, 0]));
+        assert!(!emul.is_protected(8));
+    }
+}
@@ -0,0 +1,296 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+//!
+//! A C-compatible interface for firmware and diagnostics not written in
+//! Rust.  All types are `#[repr(C)]` and all functions are `extern "C"` with
+//! unmangled `spd_`-prefixed names, suitable for cbindgen.
+//!
+
+use crate::{
+    ddr5, verify_crc, DeviceType, Function, Page, SelectAddress, Summary,
+    PART_NUMBER_LEN,
+};
+use core::slice;
+
+pub const SPD_FUNCTION_TEMPERATURE: u8 = 0;
+pub const SPD_FUNCTION_MEMORY: u8 = 1;
+pub const SPD_FUNCTION_PROTECTION_STATUS: u8 = 2;
+pub const SPD_FUNCTION_CLEAR_ALL_WRITE_PROTECTION: u8 = 3;
+pub const SPD_FUNCTION_PAGE_ADDRESS: u8 = 4;
+
+///
+/// A function as described in Table 2 of EE1004:  `kind` is one of the
+/// `SPD_FUNCTION_*` constants and `arg` is the select address, block or page
+/// (as applicable).
+///
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpdFunction {
+    pub kind: u8,
+    pub arg: u8,
+}
+
+impl From<Function> for SpdFunction {
+    fn from(func: Function) -> Self {
+        let (kind, arg) = match func {
+            Function::Temperature(sa) => (SPD_FUNCTION_TEMPERATURE, sa.get()),
+            Function::Memory(sa) => (SPD_FUNCTION_MEMORY, sa.get()),
+            Function::ProtectionStatus(block) => {
+                (SPD_FUNCTION_PROTECTION_STATUS, block)
+            }
+            Function::ClearAllWriteProtection => {
+                (SPD_FUNCTION_CLEAR_ALL_WRITE_PROTECTION, 0)
+            }
+            Function::PageAddress(page) => (SPD_FUNCTION_PAGE_ADDRESS, page.0),
+        };
+
+        Self { kind, arg }
+    }
+}
+
+impl SpdFunction {
+    fn to_function(self) -> Option<Function> {
+        match self.kind {
+            SPD_FUNCTION_TEMPERATURE => {
+                Some(Function::Temperature(SelectAddress::try_new(self.arg)?))
+            }
+            SPD_FUNCTION_MEMORY => {
+                Some(Function::Memory(SelectAddress::try_new(self.arg)?))
+            }
+            SPD_FUNCTION_PROTECTION_STATUS => {
+                Some(Function::ProtectionStatus(self.arg))
+            }
+            SPD_FUNCTION_CLEAR_ALL_WRITE_PROTECTION => {
+                Some(Function::ClearAllWriteProtection)
+            }
+            SPD_FUNCTION_PAGE_ADDRESS => {
+                Some(Function::PageAddress(Page(self.arg)))
+            }
+            _ => None,
+        }
+    }
+}
+
+///
+/// A C rendition of [`Summary`].  The part number is NUL-terminated with
+/// padding removed; `capacity` is 0 if unknown; `crc_valid` is -1 if the
+/// CRCs were not checked.
+///
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpdSummary {
+    pub device_type: u8,
+    pub module_type: u8,
+    pub module_manufacturer: u16,
+    pub dram_manufacturer: u16,
+    pub year: u8,
+    pub week: u8,
+    pub serial: u32,
+    pub tck_min_ps: u32,
+    pub taa_min_ps: u32,
+    pub capacity: u64,
+    pub crc_valid: i8,
+    pub part_number: [u8; PART_NUMBER_LEN + 1],
+}
+
+impl From<&Summary> for SpdSummary {
+    fn from(summary: &Summary) -> Self {
+        let mut part_number = [0u8; PART_NUMBER_LEN + 1];
+        let pn = summary.part_number();
+        part_number[..pn.len()].copy_from_slice(pn);
+
+        Self {
+            device_type: summary.device_type as u8,
+            module_type: summary.module_type,
+            module_manufacturer: summary.module_manufacturer,
+            dram_manufacturer: summary.dram_manufacturer,
+            year: summary.year,
+            week: summary.week,
+            serial: summary.serial,
+            tck_min_ps: summary.tck_min.0,
+            taa_min_ps: summary.taa_min.0,
+            capacity: summary.capacity.unwrap_or(0),
+            crc_valid: match summary.crc_valid {
+                Some(true) => 1,
+                Some(false) => 0,
+                None => -1,
+            },
+            part_number,
+        }
+    }
+}
+
+///
+/// Convert a function to its device code.  Returns false if the function is
+/// invalid.
+///
+/// # Safety
+///
+/// `code` must be a valid pointer to writable memory.
+///
+#[no_mangle]
+pub unsafe extern "C" fn spd_function_to_device_code(
+    func: SpdFunction,
+    code: *mut u8,
+) -> bool {
+    match func.to_function().and_then(Function::to_device_code) {
+        Some(c) if !code.is_null() => {
+            *code = c;
+            true
+        }
+        _ => false,
+    }
+}
+
+///
+/// Convert a device code to its function.  Returns false if the device code
+/// does not correspond to a function.
+///
+/// # Safety
+///
+/// `func` must be a valid pointer to writable memory.
+///
+#[no_mangle]
+pub unsafe extern "C" fn spd_function_from_device_code(
+    code: u8,
+    func: *mut SpdFunction,
+) -> bool {
+    match Function::from_device_code(code) {
+        Some(f) if !func.is_null() => {
+            *func = f.into();
+            true
+        }
+        _ => false,
+    }
+}
+
+///
+/// Verify the CRCs of an image, as located by its device type.  Returns
+/// false if the device type is not recognized.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes.
+///
+#[no_mangle]
+pub unsafe extern "C" fn spd_crc_verify(buf: *const u8, len: usize) -> bool {
+    if buf.is_null() || len < crate::PAGE_SIZE {
+        return false;
+    }
+
+    let buf = slice::from_raw_parts(buf, len);
+
+    match DeviceType::from_spd(buf) {
+        Some(DeviceType::Ddr4 | DeviceType::Ddr4E) => verify_crc(buf),
+        Some(DeviceType::Ddr5) => ddr5::verify_crc(buf),
+        None => false,
+    }
+}
+
+///
+/// Decode a summary of an image.  Returns false if the image is shorter
+/// than a page or could not be decoded.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes and `summary` must be a valid
+/// pointer to writable memory.
+///
+#[no_mangle]
+pub unsafe extern "C" fn spd_summary(
+    buf: *const u8,
+    len: usize,
+    summary: *mut SpdSummary,
+) -> bool {
+    if buf.is_null() || summary.is_null() || len < crate::PAGE_SIZE {
+        return false;
+    }
+
+    match Summary::from_spd(slice::from_raw_parts(buf, len)) {
+        Some(s) => {
+            *summary = (&s).into();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{update_crc, MAX_SIZE};
+
+    #[test]
+    fn device_codes() {
+        let mut code = 0;
+        let mut func = SpdFunction { kind: 0, arg: 0 };
+
+        unsafe {
+            assert!(spd_function_to_device_code(
+                SpdFunction {
+                    kind: SPD_FUNCTION_MEMORY,
+                    arg: 1
+                },
+                &mut code
+            ));
+            assert_eq!(code, 0x51);
+
+            assert!(!spd_function_to_device_code(
+                SpdFunction { kind: 9, arg: 0 },
+                &mut code
+            ));
+
+            assert!(spd_function_from_device_code(0x37, &mut func));
+            assert_eq!(func.kind, SPD_FUNCTION_PAGE_ADDRESS);
+            assert_eq!(func.arg, 1);
+
+            assert!(!spd_function_from_device_code(0x7f, &mut func));
+        }
+    }
+
+    #[test]
+    fn summary() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x149..0x14c].copy_from_slice(b"ABC");
+        update_crc(&mut buf);
+
+        let mut summary = core::mem::MaybeUninit::<SpdSummary>::uninit();
+
+        unsafe {
+            assert!(spd_crc_verify(buf.as_ptr(), buf.len()));
+            assert!(spd_summary(buf.as_ptr(), buf.len(), summary.as_mut_ptr()));
+
+            let summary = summary.assume_init();
+            assert_eq!(summary.device_type, 0x0c);
+            assert_eq!(summary.crc_valid, 1);
+            assert_eq!(&summary.part_number[..4], b"ABC\0");
+
+            let mut summary = core::mem::MaybeUninit::<SpdSummary>::uninit();
+            assert!(!spd_summary(buf.as_ptr(), 2, summary.as_mut_ptr()));
+            assert!(!spd_summary(buf.as_ptr(), 0, summary.as_mut_ptr()));
+        }
+    }
+
+    #[test]
+    fn ddr5() {
+        let mut buf = ddr5::Builder::new(0x01).build().unwrap();
+        let mut summary = core::mem::MaybeUninit::<SpdSummary>::uninit();
+
+        unsafe {
+            assert!(spd_crc_verify(buf.as_ptr(), buf.len()));
+            assert!(spd_summary(buf.as_ptr(), buf.len(), summary.as_mut_ptr()));
+            assert_eq!(summary.assume_init().device_type, 0x12);
+
+            buf[0x003] ^= 1;
+            assert!(!spd_crc_verify(buf.as_ptr(), buf.len()));
+
+            buf[0x002] = 0xff;
+            assert!(!spd_crc_verify(buf.as_ptr(), buf.len()));
+        }
+    }
+}
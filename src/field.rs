@@ -0,0 +1,399 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{Error, Offset};
+
+///
+/// The padding used for string fields.
+///
+const PAD: u8 = b' ';
+
+///
+/// A field within an SPD image, which may span more than one byte.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Field {
+    /// A single byte.
+    Byte(Offset),
+
+    /// A 16-bit value split across two (not necessarily adjacent or ordered)
+    /// bytes.
+    U16 { lsb: Offset, msb: Offset },
+
+    /// A 12-bit value whose least significant byte is in `lsb` and whose
+    /// upper nibble is in bits `shift + 3..=shift` of `upper`.  The other
+    /// nibble of `upper` typically belongs to a different field.
+    U12 {
+        lsb: Offset,
+        upper: Offset,
+        shift: u8,
+    },
+
+    /// A 32-bit value stored in four consecutive bytes, most significant
+    /// byte first, as serial numbers are conventionally displayed.
+    U32(Offset),
+
+    /// A space-padded ASCII string spanning `base` through `limit`
+    /// inclusive.
+    String { base: Offset, limit: Offset },
+}
+
+///
+/// The value of a field.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Value<'a> {
+    Byte(u8),
+    U16(u16),
+    U32(u32),
+    String(&'a [u8]),
+}
+
+impl Field {
+    pub const MODULE_MANUFACTURER_ID: Field = Field::U16 {
+        lsb: Offset::ModuleManufacturerIDCodeLSB,
+        msb: Offset::ModuleManufacturerIDCodeMSB,
+    };
+
+    pub const MODULE_SERIAL_NUMBER: Field =
+        Field::U32(Offset::ModuleSerialNumber0);
+
+    pub const PART_NUMBER: Field = Field::String {
+        base: Offset::PartNumberBase,
+        limit: Offset::PartNumberLimit,
+    };
+
+    pub const DRAM_MANUFACTURER_ID: Field = Field::U16 {
+        lsb: Offset::DRAMManufacturerIDCodeLSB,
+        msb: Offset::DRAMManufacturerIDCodeMSB,
+    };
+
+    pub const CRC_BASE: Field = Field::U16 {
+        lsb: Offset::CRCBaseLSB,
+        msb: Offset::CRCBaseMSB,
+    };
+
+    pub const TRAS_MIN: Field = Field::U12 {
+        lsb: Offset::TRASMin,
+        upper: Offset::UpperNibblesTRASMin,
+        shift: 0,
+    };
+
+    pub const TRC_MIN: Field = Field::U12 {
+        lsb: Offset::TRCMin,
+        upper: Offset::UpperNibblesTRASMin,
+        shift: 4,
+    };
+
+    pub const TRFC1_MIN: Field = Field::U16 {
+        lsb: Offset::TRFC1MinLSB,
+        msb: Offset::TRFC1MinMSB,
+    };
+
+    pub const TRFC2_MIN: Field = Field::U16 {
+        lsb: Offset::TRFC2MinLSB,
+        msb: Offset::TRFC2MinMSB,
+    };
+
+    pub const TRFC4_MIN: Field = Field::U16 {
+        lsb: Offset::TRFC4MinLSB,
+        msb: Offset::TRFC4MinMSB,
+    };
+
+    pub const TFAW_MIN: Field = Field::U12 {
+        lsb: Offset::TFAWminLSB,
+        upper: Offset::TFAWminMSB,
+        shift: 0,
+    };
+
+    pub const TWR_MIN: Field = Field::U12 {
+        lsb: Offset::TWRMin,
+        upper: Offset::UpperNibbleTWRMin,
+        shift: 0,
+    };
+
+    pub const TWTRS_MIN: Field = Field::U12 {
+        lsb: Offset::TWTRSMin,
+        upper: Offset::UpperNibblesTWTRMin,
+        shift: 0,
+    };
+
+    pub const TWTRL_MIN: Field = Field::U12 {
+        lsb: Offset::TWTRLMin,
+        upper: Offset::UpperNibblesTWTRMin,
+        shift: 4,
+    };
+
+    ///
+    /// The lowest byte offset occupied by this field.
+    ///
+    pub fn base(&self) -> usize {
+        match *self {
+            Field::Byte(offset) | Field::U32(offset) => offset.to_usize(),
+            Field::U16 { lsb, msb } => lsb.to_usize().min(msb.to_usize()),
+            Field::U12 { lsb, upper, .. } => {
+                lsb.to_usize().min(upper.to_usize())
+            }
+            Field::String { base, .. } => base.to_usize(),
+        }
+    }
+
+    ///
+    /// The number of bytes spanned by this field, from [`Field::base`]
+    /// through the highest byte it occupies.  The two bytes of a `U16` or
+    /// `U12` need not be adjacent, so the span may include bytes of other
+    /// fields; see [`Field::offsets`].
+    ///
+    pub fn len(&self) -> usize {
+        match *self {
+            Field::Byte(_) => 1,
+            Field::U16 { lsb: a, msb: b }
+            | Field::U12 {
+                lsb: a, upper: b, ..
+            } => {
+                let (a, b) = (a.to_usize(), b.to_usize());
+                a.max(b) - a.min(b) + 1
+            }
+            Field::U32(_) => 4,
+            Field::String { base, limit } => {
+                limit.to_usize() - base.to_usize() + 1
+            }
+        }
+    }
+
+    ///
+    /// Returns true if the byte at the given offset belongs to this field.
+    ///
+    pub fn contains(&self, offset: usize) -> bool {
+        match *self {
+            Field::U16 { lsb: a, msb: b }
+            | Field::U12 {
+                lsb: a, upper: b, ..
+            } => offset == a.to_usize() || offset == b.to_usize(),
+            _ => (self.base()..self.base() + self.len()).contains(&offset),
+        }
+    }
+
+    ///
+    /// The offsets of the bytes that belong to this field, in ascending
+    /// order.
+    ///
+    pub fn offsets(&self) -> impl Iterator<Item = usize> {
+        let field = *self;
+
+        (field.base()..field.base() + field.len())
+            .filter(move |&offset| field.contains(offset))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get<'a>(&self, buf: &'a [u8]) -> Value<'a> {
+        match *self {
+            Field::Byte(offset) => Value::Byte(offset.within(buf)),
+            Field::U16 { lsb, msb } => Value::U16(u16::from_le_bytes([
+                lsb.within(buf),
+                msb.within(buf),
+            ])),
+            Field::U12 { .. } => Value::U16(self.get_u16(buf).unwrap()),
+            Field::U32(base) => {
+                let base = base.to_usize();
+                Value::U32(u32::from_be_bytes([
+                    buf[base],
+                    buf[base + 1],
+                    buf[base + 2],
+                    buf[base + 3],
+                ]))
+            }
+            Field::String { .. } => {
+                Value::String(&buf[self.base()..self.base() + self.len()])
+            }
+        }
+    }
+
+    ///
+    /// Set the field to the given value.  Strings shorter than the field are
+    /// padded with spaces.
+    ///
+    pub fn set(&self, buf: &mut [u8], value: Value<'_>) -> Result<(), Error> {
+        match (*self, value) {
+            (Field::Byte(offset), Value::Byte(v)) => {
+                buf[offset.to_usize()] = v;
+            }
+            (Field::U16 { .. }, Value::U16(v))
+            | (Field::U12 { .. }, Value::U16(v)) => {
+                self.set_u16(buf, v)?;
+            }
+            (Field::U32(base), Value::U32(v)) => {
+                let base = base.to_usize();
+                buf[base..base + 4].copy_from_slice(&v.to_be_bytes());
+            }
+            (Field::String { .. }, Value::String(s)) => {
+                if s.len() > self.len() {
+                    return Err(Error::ValueTooLong);
+                }
+
+                let field = &mut buf[self.base()..self.base() + self.len()];
+                field[..s.len()].copy_from_slice(s);
+                field[s.len()..].iter_mut().for_each(|b| *b = PAD);
+            }
+            _ => return Err(Error::TypeMismatch),
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// For 16-bit and 12-bit fields, assemble the value from its constituent
+    /// bytes (or nibbles).
+    ///
+    pub fn get_u16(&self, buf: &[u8]) -> Option<u16> {
+        match *self {
+            Field::U16 { lsb, msb } => {
+                Some(u16::from_le_bytes([lsb.within(buf), msb.within(buf)]))
+            }
+            Field::U12 { lsb, upper, shift } => {
+                let nibble = (upper.within(buf) >> shift) & 0xf;
+                Some(u16::from_le_bytes([lsb.within(buf), nibble]))
+            }
+            _ => None,
+        }
+    }
+
+    ///
+    /// For 16-bit and 12-bit fields, split the value into its constituent
+    /// bytes (or nibbles).  For 12-bit fields, the other nibble of the
+    /// shared byte is preserved.
+    ///
+    pub fn set_u16(&self, buf: &mut [u8], v: u16) -> Result<(), Error> {
+        let [l, m] = v.to_le_bytes();
+
+        match *self {
+            Field::U16 { lsb, msb } => {
+                buf[lsb.to_usize()] = l;
+                buf[msb.to_usize()] = m;
+            }
+            Field::U12 { lsb, upper, shift } => {
+                if m > 0xf {
+                    return Err(Error::ValueTooLong);
+                }
+
+                let upper = &mut buf[upper.to_usize()];
+                *upper = (*upper & !(0xf << shift)) | (m << shift);
+                buf[lsb.to_usize()] = l;
+            }
+            _ => return Err(Error::TypeMismatch),
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Value<'a> {
+    ///
+    /// For string values, the string with trailing padding removed.
+    ///
+    pub fn trimmed(&self) -> Option<&'a [u8]> {
+        match *self {
+            Value::String(s) => {
+                let len = s
+                    .iter()
+                    .rposition(|&b| b != PAD && b != 0)
+                    .map_or(0, |p| p + 1);
+                Some(&s[..len])
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    #[test]
+    fn fields() {
+        let mut buf = [0u8; MAX_SIZE];
+
+        Field::MODULE_MANUFACTURER_ID
+            .set(&mut buf, Value::U16(0xce80))
+            .unwrap();
+        assert_eq!(buf[0x140], 0x80);
+        assert_eq!(buf[0x141], 0xce);
+        assert_eq!(Field::MODULE_MANUFACTURER_ID.get(&buf), Value::U16(0xce80));
+
+        Field::MODULE_SERIAL_NUMBER
+            .set(&mut buf, Value::U32(0x1234_5678))
+            .unwrap();
+        assert_eq!(&buf[0x145..0x149], &[0x12, 0x34, 0x56, 0x78]);
+
+        Field::PART_NUMBER
+            .set(&mut buf, Value::String(b"M393A4K40DB3-CWE"))
+            .unwrap();
+        assert_eq!(Field::PART_NUMBER.len(), 20);
+        assert_eq!(&buf[0x159..0x15d], b"    ");
+        assert_eq!(
+            Field::PART_NUMBER.get(&buf).trimmed(),
+            Some(&b"M393A4K40DB3-CWE"[..])
+        );
+
+        assert_eq!(
+            Field::PART_NUMBER.set(&mut buf, Value::String(&[b'x'; 21])),
+            Err(Error::ValueTooLong)
+        );
+        assert_eq!(
+            Field::CRC_BASE.set(&mut buf, Value::Byte(0)),
+            Err(Error::TypeMismatch)
+        );
+    }
+
+    #[test]
+    fn pairs() {
+        let mut buf = [0u8; MAX_SIZE];
+
+        Field::TRAS_MIN.set_u16(&mut buf, 0x123).unwrap();
+        Field::TRC_MIN.set_u16(&mut buf, 0xabc).unwrap();
+        assert_eq!(buf[0x1b], 0xa1);
+        assert_eq!(buf[0x1c], 0x23);
+        assert_eq!(buf[0x1d], 0xbc);
+        assert_eq!(Field::TRAS_MIN.get_u16(&buf), Some(0x123));
+        assert_eq!(Field::TRC_MIN.get(&buf), Value::U16(0xabc));
+
+        //
+        // tRCmin's upper nibble is two bytes below its LSB, with tRASmin's
+        // LSB in between.
+        //
+        assert_eq!(Field::TRC_MIN.base(), 0x1b);
+        assert_eq!(Field::TRC_MIN.len(), 3);
+        assert!(!Field::TRC_MIN.contains(0x1c));
+        let mut offsets = Field::TRC_MIN.offsets();
+        assert_eq!(offsets.next(), Some(0x1b));
+        assert_eq!(offsets.next(), Some(0x1d));
+        assert_eq!(offsets.next(), None);
+        assert_eq!(Field::TRAS_MIN.offsets().count(), 2);
+        assert_eq!(Field::PART_NUMBER.offsets().count(), 20);
+
+        assert_eq!(
+            Field::TWR_MIN.set_u16(&mut buf, 0x1000),
+            Err(Error::ValueTooLong)
+        );
+
+        Field::TRFC1_MIN.set_u16(&mut buf, 0x0af0).unwrap();
+        assert_eq!(&buf[0x1e..0x20], &[0xf0, 0x0a]);
+
+        buf[0x24] = 0x01;
+        buf[0x25] = 0x50;
+        assert_eq!(Field::TFAW_MIN.get_u16(&buf), Some(0x150));
+
+        Field::TWTRL_MIN.set(&mut buf, Value::U16(0x2ff)).unwrap();
+        Field::TWTRS_MIN.set(&mut buf, Value::U16(0x104)).unwrap();
+        assert_eq!(&buf[0x2b..0x2e], &[0x21, 0x04, 0xff]);
+
+        assert_eq!(Field::PART_NUMBER.get_u16(&buf), None);
+    }
+}
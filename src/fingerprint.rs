@@ -0,0 +1,111 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{classify, ddr5, DeviceType, Offset};
+use core::ops::RangeInclusive;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(bytes: impl Iterator<Item = u8>) -> u64 {
+    bytes.fold(FNV_OFFSET_BASIS, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+///
+/// The bytes that are unique to each manufactured module: location, date and
+/// serial number.  These are contiguous in both DDR4 and DDR5.
+///
+pub fn per_unit_range(device: Option<DeviceType>) -> RangeInclusive<usize> {
+    match device {
+        Some(DeviceType::Ddr5) => {
+            ddr5::Offset::ModuleManufacturingLocation.to_usize()
+                ..=ddr5::Offset::ModuleSerialNumber3.to_usize()
+        }
+        _ => {
+            Offset::ModuleManufacturingLocation.to_usize()
+                ..=Offset::ModuleSerialNumber3.to_usize()
+        }
+    }
+}
+
+///
+/// A 64-bit FNV-1a hash of the SPD content with the per-unit bytes and any
+/// per-unit region (as determined by the device type in the image) treated
+/// as zero.  Modules of the same SKU will have the same fingerprint.
+///
+pub fn fingerprint(buf: &[u8]) -> u64 {
+    let device = DeviceType::from_spd(buf);
+    let mask = per_unit_range(device);
+    let per_unit = |i| {
+        mask.contains(&i)
+            || classify(device.unwrap_or(DeviceType::Ddr4), i)
+                .is_some_and(|r| r.is_per_unit())
+    };
+
+    fnv1a(
+        buf.iter()
+            .enumerate()
+            .map(|(i, &b)| if per_unit(i) { 0 } else { b }),
+    )
+}
+
+///
+/// A 64-bit FNV-1a hash of the entire SPD content.
+///
+pub fn content_hash(buf: &[u8]) -> u64 {
+    fnv1a(buf.iter().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    #[test]
+    fn fnv() {
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(content_hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn ddr4() {
+        let mut a = [0u8; MAX_SIZE];
+        a[0x002] = 0x0c;
+        a[0x149] = b'M';
+
+        let mut b = a;
+        b[0x142] = 0x01;
+        b[0x143] = 0x21;
+        b[0x148] = 0xff;
+
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+        assert_ne!(content_hash(&a), content_hash(&b));
+
+        b[0x180] = b'A';
+        b[0x1c0] = b'A';
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+
+        b[0x149] = b'X';
+        assert_ne!(fingerprint(&a), fingerprint(&b));
+    }
+
+    #[test]
+    fn ddr5() {
+        let mut a = [0u8; 1024];
+        a[0x002] = 0x12;
+
+        let mut b = a;
+        b[0x205] = 0x12;
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+
+        b[0x145] = 0x12;
+        assert_ne!(fingerprint(&a), fingerprint(&b));
+    }
+}
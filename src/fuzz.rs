@@ -0,0 +1,226 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+//
+// Implementations of `arbitrary::Arbitrary` that generate structurally
+// plausible images:  a known device type, encodable organizations, timings
+// near those of real speed bins, and valid CRCs.  Fuzzers that want to
+// exercise CRC or decode failures can corrupt the result themselves.
+//
+
+use crate::ddr5::{self, Organization, Sdram};
+use crate::{
+    update_crc, DeviceType, Field, Offset, Picoseconds, SpdImage, Timing,
+    DDR5_MAX_SIZE, MAX_SIZE,
+};
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+///
+/// tCKmin of the DDR4 speed bins, from DDR4-3200 to DDR4-1600.
+///
+const DDR4_TCK: &[u32] = &[625, 682, 750, 833, 937, 1071, 1250];
+
+///
+/// tCKmin of the DDR5 speed bins, from DDR5-6400 to DDR5-3200.
+///
+const DDR5_TCK: &[u32] = &[312, 357, 416, 454, 500, 555, 625];
+
+fn ps(u: &mut Unstructured<'_>, range: (u32, u32)) -> Result<Picoseconds> {
+    Ok(Picoseconds(u.int_in_range(range.0..=range.1)?))
+}
+
+fn bcd(u: &mut Unstructured<'_>, max: u8) -> Result<u8> {
+    let value = u.int_in_range(0..=max)?;
+    Ok(((value / 10) << 4) | (value % 10))
+}
+
+///
+/// Fill a part number with printable ASCII, padded with spaces.
+///
+fn part_number(u: &mut Unstructured<'_>, field: &mut [u8]) -> Result<usize> {
+    let len = u.int_in_range(0..=field.len())?;
+
+    field.fill(b' ');
+
+    for byte in field[..len].iter_mut() {
+        *byte = u.int_in_range(b' '..=b'~')?;
+    }
+
+    Ok(len)
+}
+
+impl<'a> Arbitrary<'a> for ddr5::Builder {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let tck = *u.choose(DDR5_TCK)?;
+        let taa = ps(u, (13_000, 18_000))?;
+        let trp = ps(u, (13_000, 18_000))?;
+        let tras = ps(u, (32_000, 35_000))?;
+
+        let organization = Organization {
+            channels: 2,
+            bus_width: 32,
+            ecc_width: *u.choose(&[0, 4, 8])?,
+            ranks: u.int_in_range(1..=4)?,
+            first: Sdram {
+                density: *u.choose(&[16, 24, 32, 64])?,
+                dies: *u.choose(&[1, 2, 4])?,
+                width: *u.choose(&[4, 8, 16])?,
+            },
+            second: None,
+        };
+
+        let mut pn = [b' '; 30];
+        let len = part_number(u, &mut pn)?;
+
+        let mut builder = ddr5::Builder::new(*u.choose(&[1, 2, 3, 4])?)
+            .organization(organization)
+            .timing(ddr5::Timing::TCkAvgMin, Picoseconds(tck))
+            .timing(ddr5::Timing::TCkAvgMax, Picoseconds(1010))
+            .timing(ddr5::Timing::TAAMin, taa)
+            .timing(ddr5::Timing::TRCDMin, ps(u, (13_000, 18_000))?)
+            .timing(ddr5::Timing::TRPMin, trp)
+            .timing(ddr5::Timing::TRASMin, tras)
+            .timing(ddr5::Timing::TRCMin, Picoseconds(tras.0 + trp.0))
+            .timing(ddr5::Timing::TWRMin, Picoseconds(30_000))
+            .timing(ddr5::Timing::TRFC1Min, Picoseconds(295_000))
+            .cas_latencies(ddr5::CasLatencies(
+                u.int_in_range(1..=(1 << 40) - 1)?,
+            ))
+            .hub(u.arbitrary()?, u.arbitrary()?)
+            .manufacturer(u.arbitrary()?)
+            .location(u.arbitrary()?)
+            .date(bcd(u, 99)?, bcd(u, 52)?)
+            .serial(u.arbitrary()?)
+            .part_number(&pn[..len])
+            .revision(u.arbitrary()?)
+            .dram_manufacturer(u.arbitrary()?, u.arbitrary()?);
+
+        if u.arbitrary()? {
+            builder = builder.temperature_sensors(
+                u.arbitrary()?,
+                u.arbitrary()?,
+                u.int_in_range(1..=2)?,
+            );
+        }
+
+        Ok(builder)
+    }
+}
+
+impl<'a> Arbitrary<'a> for SpdImage<DDR5_MAX_SIZE> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let builder = ddr5::Builder::arbitrary(u)?;
+        let image = builder.build().map_err(|_| Error::IncorrectFormat)?;
+        Ok(SpdImage::new(image))
+    }
+}
+
+impl<'a> Arbitrary<'a> for SpdImage<MAX_SIZE> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut buf = [0u8; MAX_SIZE];
+        let mut set = |offset: Offset, value| buf[offset.to_usize()] = value;
+
+        //
+        // A 512-byte device with 384 bytes used, SPD revision 1.1.
+        //
+        set(Offset::SPDDeviceSize, 0x23);
+        set(Offset::SPDRevision, 0x11);
+        set(Offset::DRAMDeviceType, DeviceType::Ddr4 as u8);
+        set(Offset::ModuleType, *u.choose(&[1, 2, 3, 4])?);
+        set(
+            Offset::SDRAMDensity,
+            (*u.choose(&[0b01, 0b10])? << 6) | *u.choose(&[4, 5, 6])?,
+        );
+        set(Offset::PrimarySDRAMPackageType, *u.choose(&[0x00, 0x91])?);
+        set(Offset::ModuleNominalVoltage, 0x03);
+        set(
+            Offset::ModuleOrganization,
+            (u.int_in_range(0..=3)? << 3) | u.int_in_range(0..=2)?,
+        );
+        set(Offset::ModuleMemoryBusWidth, *u.choose(&[0x03, 0x0b])?);
+        set(Offset::ModuleManufacturerIDCodeLSB, u.arbitrary()?);
+        set(Offset::ModuleManufacturerIDCodeMSB, u.arbitrary()?);
+        set(Offset::ModuleManufacturingLocation, u.arbitrary()?);
+        set(Offset::ModuleManufacturingDateYear, bcd(u, 99)?);
+        set(Offset::ModuleManufacturingDateWeek, bcd(u, 52)?);
+        set(Offset::ModuleRevisionCode, u.arbitrary()?);
+        set(Offset::DRAMManufacturerIDCodeLSB, u.arbitrary()?);
+        set(Offset::DRAMManufacturerIDCodeMSB, u.arbitrary()?);
+        set(Offset::DRAMStepping, u.arbitrary()?);
+
+        let serial: u32 = u.arbitrary()?;
+        let s = Offset::ModuleSerialNumber0.to_usize();
+        buf[s..s + 4].copy_from_slice(&serial.to_be_bytes());
+
+        let base = Offset::PartNumberBase.to_usize();
+        let limit = Offset::PartNumberLimit.to_usize();
+        part_number(u, &mut buf[base..=limit])?;
+
+        let cl: u32 = u.arbitrary()?;
+        let c = Offset::CASLatencies0.to_usize();
+        buf[c..c + 4].copy_from_slice(&(cl | 1).to_le_bytes());
+
+        let tck = *u.choose(DDR4_TCK)?;
+        let trp = ps(u, (12_500, 15_000))?;
+        let timings = [
+            (Timing::TCkAvgMin, Picoseconds(tck)),
+            (Timing::TCkAvgMax, Picoseconds(1600)),
+            (Timing::TAAMin, ps(u, (12_500, 15_000))?),
+            (Timing::TRCDMin, ps(u, (12_500, 15_000))?),
+            (Timing::TRPMin, trp),
+        ];
+
+        for &(timing, ps) in timings.iter() {
+            timing.set(&mut buf, ps).ok_or(Error::IncorrectFormat)?;
+        }
+
+        //
+        // tRAS and tRC are in units of the medium timebase (125 ps).
+        //
+        let tras = u.int_in_range(256..=280)?;
+        let trc = tras + (trp.0 as u16).div_ceil(125);
+        Field::TRAS_MIN.set_u16(&mut buf, tras).unwrap();
+        Field::TRC_MIN.set_u16(&mut buf, trc).unwrap();
+
+        update_crc(&mut buf);
+        Ok(SpdImage::new(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{validate_spd, Summary};
+
+    #[test]
+    fn plausible() {
+        let mut data = [0u8; 256];
+
+        for seed in 0..32u8 {
+            data.iter_mut()
+                .enumerate()
+                .for_each(|(i, b)| *b = (i as u8).wrapping_mul(seed) ^ seed);
+
+            let mut u = Unstructured::new(&data);
+            let ddr4 = SpdImage::<MAX_SIZE>::arbitrary(&mut u).unwrap();
+            let ddr5 = SpdImage::<DDR5_MAX_SIZE>::arbitrary(&mut u).unwrap();
+
+            for image in [&ddr4.as_bytes()[..], &ddr5.as_bytes()[..]].iter() {
+                assert_eq!(validate_spd(image), Ok(()));
+
+                let summary = Summary::from_spd(image).unwrap();
+                assert!(summary.capacity.is_some());
+                assert!(summary.tck_min.0 >= 312);
+            }
+        }
+
+        //
+        // An exhausted input still yields an image.
+        //
+        let mut u = Unstructured::new(&[]);
+        assert!(SpdImage::<MAX_SIZE>::arbitrary(&mut u).is_ok());
+    }
+}
@@ -0,0 +1,120 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{DeviceType, KeyByte, ModuleIdentity, DDR5_MAX_SIZE};
+
+///
+/// A best-effort view of an image of any device type, including those
+/// newer than this crate:  raw access to its bytes, along with its identity
+/// as decoded from its presumed layout.  This allows modules to be
+/// inventoried before their device type is fully supported.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GenericView<'a> {
+    key: KeyByte,
+    buf: &'a [u8],
+}
+
+impl<'a> GenericView<'a> {
+    ///
+    /// Returns `None` only if the image is too short to have a key byte.
+    ///
+    pub fn new(buf: &'a [u8]) -> Option<Self> {
+        Some(Self {
+            key: KeyByte::from_spd(buf)?,
+            buf,
+        })
+    }
+
+    pub fn key(&self) -> KeyByte {
+        self.key
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    pub fn get(&self, offset: impl Into<usize>) -> Option<u8> {
+        self.buf.get(offset.into()).copied()
+    }
+
+    ///
+    /// The layout used to locate fields.  For an unknown device type, this
+    /// is inferred from the size of the image:  the 1024-byte layout of
+    /// DDR5 has been carried forward by later device types, while smaller
+    /// images are presumed to be laid out as DDR4.
+    ///
+    pub fn layout(&self) -> DeviceType {
+        match self.key {
+            KeyByte::Known(device_type) => device_type.layout(),
+            KeyByte::Unknown(_) if self.buf.len() >= DDR5_MAX_SIZE => {
+                DeviceType::Ddr5
+            }
+            KeyByte::Unknown(_) => DeviceType::Ddr4,
+        }
+    }
+
+    ///
+    /// The identity, decoded from the presumed layout.  Returns `None` if
+    /// the image is too short.
+    ///
+    pub fn identity(&self) -> Option<ModuleIdentity> {
+        ModuleIdentity::decode(self.buf, self.layout())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ddr5, validate_spd, Summary, MAX_SIZE};
+
+    #[test]
+    fn ddr4e() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0e;
+        buf[0x145..0x149].copy_from_slice(&[0, 0, 0, 7]);
+        crate::update_crc(&mut buf);
+
+        assert_eq!(validate_spd(&buf), Ok(()));
+
+        let view = GenericView::new(&buf).unwrap();
+        assert_eq!(view.key(), KeyByte::Known(DeviceType::Ddr4E));
+        assert_eq!(view.layout(), DeviceType::Ddr4);
+
+        let summary = Summary::from_spd(&buf).unwrap();
+        assert_eq!(summary.device_type, DeviceType::Ddr4E);
+        assert_eq!(Some(summary.serial), view.identity().map(|i| i.serial));
+    }
+
+    #[test]
+    fn unknown() {
+        //
+        // A newer device type with the 1024-byte layout.
+        //
+        let mut image = ddr5::Builder::new(0x01)
+            .serial(0x1234)
+            .part_number(b"FUTURE-PART")
+            .build()
+            .unwrap();
+        image[0x002] = 0x1f;
+
+        assert_eq!(Summary::from_spd(&image), None);
+
+        let view = GenericView::new(&image).unwrap();
+        assert_eq!(view.key(), KeyByte::Unknown(0x1f));
+        assert_eq!(view.key().device_type(), None);
+        assert_eq!(view.layout(), DeviceType::Ddr5);
+        assert_eq!(view.get(0x002usize), Some(0x1f));
+
+        let identity = view.identity().unwrap();
+        assert_eq!(identity.serial, 0x1234);
+        assert_eq!(identity.part_number(), b"FUTURE-PART");
+
+        let view = GenericView::new(&image[..MAX_SIZE]).unwrap();
+        assert_eq!(view.layout(), DeviceType::Ddr4);
+        assert_eq!(GenericView::new(&image[..2]), None);
+    }
+}
@@ -0,0 +1,207 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{classify, ddr5, DeviceType, Offset, Region};
+
+///
+/// Which per-unit fields to ignore when comparing an image against a golden
+/// image.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GoldenPolicy {
+    pub ignore_serial: bool,
+    pub ignore_date: bool,
+    pub ignore_location: bool,
+    pub ignore_end_user: bool,
+}
+
+impl GoldenPolicy {
+    ///
+    /// Compare every byte.
+    ///
+    pub const EXACT: GoldenPolicy = GoldenPolicy {
+        ignore_serial: false,
+        ignore_date: false,
+        ignore_location: false,
+        ignore_end_user: false,
+    };
+
+    ///
+    /// Ignore everything that legitimately differs between modules of the
+    /// same SKU.
+    ///
+    pub const PER_UNIT: GoldenPolicy = GoldenPolicy {
+        ignore_serial: true,
+        ignore_date: true,
+        ignore_location: true,
+        ignore_end_user: true,
+    };
+
+    fn ignores(&self, device: Option<DeviceType>, offset: usize) -> bool {
+        let (serial, date, location) = match device {
+            Some(DeviceType::Ddr5) => (
+                ddr5::Offset::ModuleSerialNumber0.to_usize()
+                    ..=ddr5::Offset::ModuleSerialNumber3.to_usize(),
+                ddr5::Offset::ModuleManufacturingDateYear.to_usize()
+                    ..=ddr5::Offset::ModuleManufacturingDateWeek.to_usize(),
+                ddr5::Offset::ModuleManufacturingLocation.to_usize(),
+            ),
+            _ => (
+                Offset::ModuleSerialNumber0.to_usize()
+                    ..=Offset::ModuleSerialNumber3.to_usize(),
+                Offset::ModuleManufacturingDateYear.to_usize()
+                    ..=Offset::ModuleManufacturingDateWeek.to_usize(),
+                Offset::ModuleManufacturingLocation.to_usize(),
+            ),
+        };
+
+        let end_user = classify(device.unwrap_or(DeviceType::Ddr4), offset)
+            == Some(Region::EndUser);
+
+        (self.ignore_serial && serial.contains(&offset))
+            || (self.ignore_date && date.contains(&offset))
+            || (self.ignore_location && offset == location)
+            || (self.ignore_end_user && end_user)
+    }
+}
+
+impl Default for GoldenPolicy {
+    fn default() -> Self {
+        GoldenPolicy::PER_UNIT
+    }
+}
+
+///
+/// A difference between an image and its golden image.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// The images differ in length.
+    Length { expected: usize, actual: usize },
+
+    /// A byte not excluded by the policy differs.
+    Byte {
+        offset: usize,
+        expected: u8,
+        actual: u8,
+    },
+}
+
+///
+/// Iterate over the differences between `image` and `golden` that are not
+/// excluded by `policy`.  The generation (and therefore the location of the
+/// per-unit fields) is taken from the golden image.
+///
+pub fn golden_violations<'a>(
+    image: &'a [u8],
+    golden: &'a [u8],
+    policy: GoldenPolicy,
+) -> impl Iterator<Item = Violation> + 'a {
+    let device = DeviceType::from_spd(golden);
+
+    let length = if image.len() != golden.len() {
+        Some(Violation::Length {
+            expected: golden.len(),
+            actual: image.len(),
+        })
+    } else {
+        None
+    };
+
+    length.into_iter().chain(
+        image
+            .iter()
+            .zip(golden.iter())
+            .enumerate()
+            .filter(move |&(offset, (actual, expected))| {
+                actual != expected && !policy.ignores(device, offset)
+            })
+            .map(|(offset, (&actual, &expected))| Violation::Byte {
+                offset,
+                expected,
+                actual,
+            }),
+    )
+}
+
+///
+/// Returns true if `image` matches `golden` in every byte not excluded by
+/// `policy`.
+///
+pub fn matches_golden(
+    image: &[u8],
+    golden: &[u8],
+    policy: GoldenPolicy,
+) -> bool {
+    golden_violations(image, golden, policy).next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    #[test]
+    fn golden() {
+        let mut golden = [0u8; MAX_SIZE];
+        golden[0x002] = 0x0c;
+
+        let mut image = golden;
+        image[0x142] = 0x01;
+        image[0x143] = 0x21;
+        image[0x146] = 0x42;
+        image[0x1c0] = b'A';
+
+        assert!(matches_golden(&image, &golden, GoldenPolicy::PER_UNIT));
+        assert!(!matches_golden(&image, &golden, GoldenPolicy::EXACT));
+        assert_eq!(
+            golden_violations(&image, &golden, GoldenPolicy::EXACT).count(),
+            4
+        );
+
+        let policy = GoldenPolicy {
+            ignore_serial: false,
+            ..GoldenPolicy::PER_UNIT
+        };
+        let mut violations = golden_violations(&image, &golden, policy);
+        assert_eq!(
+            violations.next(),
+            Some(Violation::Byte {
+                offset: 0x146,
+                expected: 0,
+                actual: 0x42
+            })
+        );
+        assert_eq!(violations.next(), None);
+        drop(violations);
+
+        image[0x149] = b'M';
+        assert!(!matches_golden(&image, &golden, GoldenPolicy::PER_UNIT));
+
+        assert_eq!(
+            golden_violations(&image[..256], &golden, GoldenPolicy::PER_UNIT)
+                .next(),
+            Some(Violation::Length {
+                expected: MAX_SIZE,
+                actual: 256
+            })
+        );
+    }
+
+    #[test]
+    fn golden_ddr5() {
+        let mut golden = [0u8; 1024];
+        golden[0x002] = 0x12;
+
+        let mut image = golden;
+        image[0x205] = 0x01;
+        image[0x300] = 0x01;
+        assert!(matches_golden(&image, &golden, GoldenPolicy::PER_UNIT));
+
+        image[0x145] = 0x01;
+        assert!(!matches_golden(&image, &golden, GoldenPolicy::PER_UNIT));
+    }
+}
@@ -0,0 +1,351 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+//!
+//! Import and export of SPD images as Intel HEX and Motorola S-records, the
+//! formats used by EEPROM programmers and by vendors to exchange SPD content.
+//!
+
+use core::fmt;
+
+///
+/// Number of data bytes emitted per record.
+///
+const RECORD_SIZE: usize = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HexError {
+    /// A record is missing its start code or is too short.
+    BadRecord(usize),
+    /// A record contains a character that isn't a hex digit.
+    BadDigit(usize),
+    /// A record's length doesn't match its byte count.
+    BadLength(usize),
+    /// A record's checksum doesn't match its contents.
+    BadChecksum(usize),
+    /// A record has an unsupported type.
+    BadType(usize),
+    /// A record addresses data beyond the end of the image.
+    AddressOutOfRange(usize),
+    /// The input ended without an end-of-file/termination record.
+    MissingEnd,
+}
+
+fn digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+///
+/// Decode the hex digits of a record (after its start code) into `bytes`,
+/// returning the decoded prefix.
+///
+fn decode<'a>(
+    line: usize,
+    digits: &[u8],
+    bytes: &'a mut [u8],
+) -> Result<&'a [u8], HexError> {
+    if !digits.len().is_multiple_of(2) || digits.len() / 2 > bytes.len() {
+        return Err(HexError::BadLength(line));
+    }
+
+    for (i, pair) in digits.chunks(2).enumerate() {
+        match (digit(pair[0]), digit(pair[1])) {
+            (Some(hi), Some(lo)) => bytes[i] = (hi << 4) | lo,
+            _ => return Err(HexError::BadDigit(line)),
+        }
+    }
+
+    Ok(&bytes[..digits.len() / 2])
+}
+
+fn store(
+    line: usize,
+    image: &mut [u8],
+    address: usize,
+    data: &[u8],
+) -> Result<usize, HexError> {
+    let limit = address + data.len();
+
+    if limit > image.len() {
+        return Err(HexError::AddressOutOfRange(line));
+    }
+
+    image[address..limit].copy_from_slice(data);
+    Ok(limit)
+}
+
+fn lines(input: &str) -> impl Iterator<Item = (usize, &[u8])> {
+    input
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(i, l)| (i + 1, l.as_bytes()))
+}
+
+///
+/// Parse Intel HEX into the given image, returning the number of bytes
+/// covered (that is, one past the highest address written).  Extended
+/// segment and linear address records are honored; start address records
+/// are ignored.
+///
+pub fn parse_ihex(input: &str, image: &mut [u8]) -> Result<usize, HexError> {
+    let mut base = 0;
+    let mut len = 0;
+
+    for (line, record) in lines(input) {
+        let mut buf = [0u8; 5 + 255];
+
+        if record.first() != Some(&b':') {
+            return Err(HexError::BadRecord(line));
+        }
+
+        let bytes = decode(line, &record[1..], &mut buf)?;
+
+        if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+            return Err(HexError::BadLength(line));
+        }
+
+        if bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0 {
+            return Err(HexError::BadChecksum(line));
+        }
+
+        let offset = u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
+        let data = &bytes[4..bytes.len() - 1];
+
+        match bytes[3] {
+            0x00 => {
+                let limit = store(line, image, base + offset, data)?;
+                len = len.max(limit);
+            }
+            0x01 => return Ok(len),
+            0x02 | 0x04 if data.len() != 2 => {
+                return Err(HexError::BadLength(line));
+            }
+            0x02 => {
+                base = (u16::from_be_bytes([data[0], data[1]]) as usize) << 4;
+            }
+            0x04 => {
+                base = (u16::from_be_bytes([data[0], data[1]]) as usize) << 16;
+            }
+            0x03 | 0x05 => {}
+            _ => return Err(HexError::BadType(line)),
+        }
+    }
+
+    Err(HexError::MissingEnd)
+}
+
+///
+/// Emit the given image as Intel HEX.
+///
+pub fn write_ihex<W: fmt::Write>(image: &[u8], w: &mut W) -> fmt::Result {
+    for (i, chunk) in image.chunks(RECORD_SIZE).enumerate() {
+        let address = ((i * RECORD_SIZE) as u16).to_be_bytes();
+        let mut sum = (chunk.len() as u8)
+            .wrapping_add(address[0])
+            .wrapping_add(address[1]);
+
+        write!(
+            w,
+            ":{:02X}{:02X}{:02X}00",
+            chunk.len(),
+            address[0],
+            address[1]
+        )?;
+
+        for &byte in chunk {
+            sum = sum.wrapping_add(byte);
+            write!(w, "{:02X}", byte)?;
+        }
+
+        writeln!(w, "{:02X}", sum.wrapping_neg())?;
+    }
+
+    writeln!(w, ":00000001FF")
+}
+
+///
+/// Parse Motorola S-records into the given image, returning the number of
+/// bytes covered.  S1, S2 and S3 data records are supported; header, count
+/// and termination records are checksummed but otherwise ignored.
+///
+pub fn parse_srec(input: &str, image: &mut [u8]) -> Result<usize, HexError> {
+    let mut len = 0;
+
+    for (line, record) in lines(input) {
+        let mut buf = [0u8; 256];
+
+        if record.len() < 4 || record[0] != b'S' {
+            return Err(HexError::BadRecord(line));
+        }
+
+        let bytes = decode(line, &record[2..], &mut buf)?;
+
+        if bytes.is_empty() || bytes.len() != bytes[0] as usize + 1 {
+            return Err(HexError::BadLength(line));
+        }
+
+        if bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0xff {
+            return Err(HexError::BadChecksum(line));
+        }
+
+        let width = match record[1] {
+            b'0' | b'1' | b'5' | b'9' => 2,
+            b'2' | b'6' | b'8' => 3,
+            b'3' | b'7' => 4,
+            _ => return Err(HexError::BadType(line)),
+        };
+
+        if bytes.len() < width + 2 {
+            return Err(HexError::BadLength(line));
+        }
+
+        let address = bytes[1..=width]
+            .iter()
+            .fold(0usize, |addr, b| (addr << 8) | *b as usize);
+        let data = &bytes[width + 1..bytes.len() - 1];
+
+        match record[1] {
+            b'1' | b'2' | b'3' => {
+                let limit = store(line, image, address, data)?;
+                len = len.max(limit);
+            }
+            b'7' | b'8' | b'9' => return Ok(len),
+            _ => {}
+        }
+    }
+
+    Err(HexError::MissingEnd)
+}
+
+fn write_srec_record<W: fmt::Write>(
+    w: &mut W,
+    kind: u8,
+    address: u16,
+    data: &[u8],
+) -> fmt::Result {
+    let count = (data.len() + 3) as u8;
+    let address = address.to_be_bytes();
+    let mut sum = count.wrapping_add(address[0]).wrapping_add(address[1]);
+
+    write!(
+        w,
+        "S{}{:02X}{:02X}{:02X}",
+        kind, count, address[0], address[1]
+    )?;
+
+    for &byte in data {
+        sum = sum.wrapping_add(byte);
+        write!(w, "{:02X}", byte)?;
+    }
+
+    writeln!(w, "{:02X}", !sum)
+}
+
+///
+/// Emit the given image as Motorola S-records (S0/S1/S5/S9).
+///
+pub fn write_srec<W: fmt::Write>(image: &[u8], w: &mut W) -> fmt::Result {
+    let mut count = 0u16;
+
+    write_srec_record(w, 0, 0, b"SPD")?;
+
+    for (i, chunk) in image.chunks(RECORD_SIZE).enumerate() {
+        write_srec_record(w, 1, (i * RECORD_SIZE) as u16, chunk)?;
+        count += 1;
+    }
+
+    write_srec_record(w, 5, count, &[])?;
+    write_srec_record(w, 9, 0, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+    extern crate std;
+    use std::string::String;
+
+    fn image() -> [u8; MAX_SIZE] {
+        let mut image = [0u8; MAX_SIZE];
+
+        for (i, byte) in image.iter_mut().enumerate() {
+            *byte = (i * 7) as u8;
+        }
+
+        image
+    }
+
+    #[test]
+    fn ihex() {
+        let image = image();
+        let mut s = String::new();
+        write_ihex(&image, &mut s).unwrap();
+        assert!(s.starts_with(":1000000000070E151C232A31383F464D545B6269A8"));
+        assert!(s.ends_with(":00000001FF\n"));
+
+        let mut parsed = [0u8; MAX_SIZE];
+        assert_eq!(parse_ihex(&s, &mut parsed), Ok(MAX_SIZE));
+        assert_eq!(parsed[..], image[..]);
+
+        let mut small = [0u8; 256];
+        assert_eq!(
+            parse_ihex(&s, &mut small),
+            Err(HexError::AddressOutOfRange(17))
+        );
+
+        let bad = s.replacen(":10000000", ":10000001", 1);
+        assert_eq!(
+            parse_ihex(&bad, &mut parsed),
+            Err(HexError::BadChecksum(1))
+        );
+
+        let truncated = s.replace(":00000001FF\n", "");
+        assert_eq!(
+            parse_ihex(&truncated, &mut parsed),
+            Err(HexError::MissingEnd)
+        );
+
+        //
+        // Address records must carry exactly two bytes.
+        //
+        for record in [":0100000200FD", ":03000004000000F9"].iter() {
+            assert_eq!(
+                parse_ihex(record, &mut parsed),
+                Err(HexError::BadLength(1))
+            );
+        }
+        assert_eq!(
+            parse_ihex(":00000006FA", &mut parsed),
+            Err(HexError::BadType(1))
+        );
+    }
+
+    #[test]
+    fn srec() {
+        let image = image();
+        let mut s = String::new();
+        write_srec(&image, &mut s).unwrap();
+        assert!(s.starts_with("S006000053504412\n"));
+        assert!(s.ends_with("S5030020DC\nS9030000FC\n"));
+
+        let mut parsed = [0u8; MAX_SIZE];
+        assert_eq!(parse_srec(&s, &mut parsed), Ok(MAX_SIZE));
+        assert_eq!(parsed[..], image[..]);
+
+        let bad = s.replacen("S113000000", "S113000001", 1);
+        assert_eq!(
+            parse_srec(&bad, &mut parsed),
+            Err(HexError::BadChecksum(2))
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{Operation, PageSelector, Planner, DDR5_MAX_SIZE, WRITE_PAGE_SIZE};
+use core::ops::{Index, IndexMut};
+
+///
+/// An owned image of `N` bytes that tracks which write pages have been
+/// modified, so that only those need be written back to the device.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpdImage<const N: usize> {
+    buf: [u8; N],
+    dirty: u64,
+}
+
+impl<const N: usize> SpdImage<N> {
+    ///
+    /// Wrap an image as read from a device; it is initially clean.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is larger than the largest SPD image.
+    ///
+    pub fn new(buf: [u8; N]) -> Self {
+        assert!(N <= DDR5_MAX_SIZE);
+        Self { buf, dirty: 0 }
+    }
+
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.buf
+    }
+
+    pub fn into_inner(self) -> [u8; N] {
+        self.buf
+    }
+
+    ///
+    /// Set a byte, marking its write page dirty if the value changes.
+    /// Returns `None` if the offset is beyond the end of the image.
+    ///
+    pub fn set(&mut self, offset: impl Into<usize>, value: u8) -> Option<()> {
+        let offset = offset.into();
+        let byte = self.buf.get_mut(offset)?;
+
+        if *byte != value {
+            *byte = value;
+            self.dirty |= 1 << (offset / WRITE_PAGE_SIZE);
+        }
+
+        Some(())
+    }
+
+    ///
+    /// Set consecutive bytes starting at the given offset.  Returns `None`
+    /// (changing nothing) if they extend beyond the end of the image.
+    ///
+    pub fn write(
+        &mut self,
+        offset: impl Into<usize>,
+        data: &[u8],
+    ) -> Option<()> {
+        let offset = offset.into();
+
+        if offset.checked_add(data.len())? > N {
+            return None;
+        }
+
+        for (i, &value) in data.iter().enumerate() {
+            self.set(offset + i, value)?;
+        }
+
+        Some(())
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty != 0
+    }
+
+    ///
+    /// The offsets of the write pages that have been modified, in ascending
+    /// order.
+    ///
+    pub fn dirty_pages(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..N.div_ceil(WRITE_PAGE_SIZE))
+            .filter(move |&p| self.dirty & (1 << p) != 0)
+            .map(|p| p * WRITE_PAGE_SIZE)
+    }
+
+    ///
+    /// Forget all modifications, typically once a flush has completed.
+    ///
+    pub fn mark_clean(&mut self) {
+        self.dirty = 0;
+    }
+
+    ///
+    /// Plan the writes of the modified write pages, and only those.  The
+    /// image remains dirty until [`SpdImage::mark_clean`] is called.
+    ///
+    pub fn flush_plan<'a, P: PageSelector>(
+        &'a self,
+        planner: &'a mut Planner<P>,
+    ) -> FlushPlan<'a, P, N> {
+        FlushPlan {
+            image: self,
+            planner,
+            remaining: self.dirty,
+            start: 0,
+            offset: 0,
+            end: 0,
+        }
+    }
+}
+
+impl<T: Into<usize>, const N: usize> Index<T> for SpdImage<N> {
+    type Output = u8;
+
+    fn index(&self, offset: T) -> &u8 {
+        &self.buf[offset.into()]
+    }
+}
+
+///
+/// Mutable indexing marks the byte's write page dirty whether or not it is
+/// actually changed; use [`SpdImage::set`] to avoid needless writes.
+///
+impl<T: Into<usize>, const N: usize> IndexMut<T> for SpdImage<N> {
+    fn index_mut(&mut self, offset: T) -> &mut u8 {
+        let offset = offset.into();
+        let byte = &mut self.buf[offset];
+        self.dirty |= 1 << (offset / WRITE_PAGE_SIZE);
+        byte
+    }
+}
+
+///
+/// An iterator over the operations needed to write back the modified pages
+/// of an [`SpdImage`].
+///
+pub struct FlushPlan<'a, P, const N: usize> {
+    image: &'a SpdImage<N>,
+    planner: &'a mut Planner<P>,
+    remaining: u64,
+    start: usize,
+    offset: usize,
+    end: usize,
+}
+
+impl<P: PageSelector, const N: usize> Iterator for FlushPlan<'_, P, N> {
+    type Item = Operation;
+
+    fn next(&mut self) -> Option<Operation> {
+        while self.offset >= self.end {
+            if self.remaining == 0 {
+                return None;
+            }
+
+            let page = self.remaining.trailing_zeros() as usize;
+            self.remaining &= !(1 << page);
+
+            self.start = page * WRITE_PAGE_SIZE;
+            self.offset = self.start;
+            self.end = core::cmp::min(self.start + WRITE_PAGE_SIZE, N);
+        }
+
+        let data = &self.image.buf[self.start..self.end];
+        self.planner.step(&mut self.offset, self.end, Some(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ddr5, execute, Ee1004Pages, Function, MockSpdDevice, Offset, RawOffset,
+        SelectAddress, Spd5118Pages, MAX_SIZE,
+    };
+
+    #[test]
+    fn dirty() {
+        let mut image = SpdImage::new([0u8; MAX_SIZE]);
+        assert!(!image.is_dirty());
+
+        image.set(Offset::TAAMin, 0).unwrap();
+        assert!(!image.is_dirty());
+
+        image.set(Offset::TAAMin, 0x6e).unwrap();
+        image[Offset::ModuleManufacturingLocation] = 3;
+        image.write(RawOffset(0x1fe), &[1, 2]).unwrap();
+        assert_eq!(image.write(0x1ffusize, &[1, 2]), None);
+        assert_eq!(image.write(usize::MAX, &[1]), None);
+        assert_eq!(image.set(RawOffset(0x200), 1), None);
+
+        assert_eq!(image[Offset::TAAMin], 0x6e);
+        assert_eq!(image[RawOffset(0x1ff)], 2);
+
+        let mut pages = [0usize; 3];
+        assert_eq!(image.dirty_pages().count(), pages.len());
+        pages
+            .iter_mut()
+            .zip(image.dirty_pages())
+            .for_each(|(p, o)| *p = o);
+        assert_eq!(pages, [0x010, 0x140, 0x1f0]);
+
+        image.mark_clean();
+        assert_eq!(image.dirty_pages().next(), None);
+    }
+
+    #[test]
+    fn flush() {
+        let sa = SelectAddress::new(0);
+        let code = Function::Memory(sa).to_device_code().unwrap();
+
+        let mut image = SpdImage::new([0u8; MAX_SIZE]);
+        image.set(Offset::TAAMin, 0x6e).unwrap();
+        image.set(Offset::DRAMStepping, 0x42).unwrap();
+
+        let mut dev = MockSpdDevice::ddr4(sa, &[0; MAX_SIZE]);
+        let mut planner = Planner::new(Ee1004Pages, code);
+
+        //
+        // A select and a write for each of the two pages.
+        //
+        assert_eq!(image.flush_plan(&mut planner).count(), 4);
+        planner.invalidate();
+        execute(&mut dev, image.flush_plan(&mut planner), &mut []).unwrap();
+        assert_eq!(dev.image(), &image.as_bytes()[..]);
+
+        //
+        // On DDR5, the unchanged bytes of a dirty write page are rewritten,
+        // but nothing else is.
+        //
+        let mut image = SpdImage::new([0u8; DDR5_MAX_SIZE]);
+        image
+            .set(ddr5::Offset::ModuleManufacturingLocation, 7)
+            .unwrap();
+
+        let mut planner = Planner::new(Spd5118Pages(sa), code);
+        let ops: [Operation; 2] = {
+            let mut plan = image.flush_plan(&mut planner);
+            [plan.next().unwrap(), plan.next().unwrap()]
+        };
+
+        assert!(matches!(ops[0], Operation::Select(_)));
+        assert_eq!(
+            ops[1],
+            Operation::Write {
+                code,
+                address: 0x80,
+                data: [0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                len: WRITE_PAGE_SIZE,
+            }
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{DeviceType, Picoseconds, Summary, MAX_DEVICES};
+
+///
+/// A configuration that platform firmware may want to reject or report.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InventoryWarning {
+    /// Both DDR4 and DDR5 modules are present.
+    MixedDeviceTypes,
+    /// Modules are from more than one manufacturer.
+    MixedVendors,
+    /// Modules have differing numbers of ranks.
+    MixedRanks,
+    /// The population of the channel differs from that of the first
+    /// populated channel.
+    AsymmetricChannel(u8),
+}
+
+///
+/// A system-level summary of the modules in up to [`MAX_DEVICES`] slots.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Inventory {
+    /// The number of populated slots.
+    pub populated: u8,
+    /// The device type of the modules, if there are any and they agree.
+    pub device_type: Option<DeviceType>,
+    /// Total capacity in bytes, if the capacity of every module is known.
+    pub capacity: Option<u64>,
+    /// The largest tCKmin of any module:  the fastest clock that all modules
+    /// support.
+    pub tck_min: Option<Picoseconds>,
+    mixed_device_types: bool,
+    mixed_vendors: bool,
+    mixed_ranks: bool,
+    asymmetric: u8,
+}
+
+impl Inventory {
+    ///
+    /// Aggregate the modules in `slots`, where consecutive runs of
+    /// `slots_per_channel` slots share a channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`MAX_DEVICES`] slots, or if
+    /// `slots_per_channel` is zero.
+    ///
+    pub fn new(slots: &[Option<Summary>], slots_per_channel: usize) -> Self {
+        assert!(slots.len() <= MAX_DEVICES as usize);
+        assert!(slots_per_channel != 0);
+
+        let modules = || slots.iter().flatten();
+        let first = modules().next();
+        let differs = |f: fn(&Summary) -> u64| {
+            first.is_some_and(|first| modules().any(|m| f(m) != f(first)))
+        };
+
+        //
+        // A channel's population is described by the capacity and ranks of
+        // each of its slots.
+        //
+        let population = |channel: &[Option<Summary>]| {
+            let mut key = [None; MAX_DEVICES as usize];
+
+            for (k, slot) in key.iter_mut().zip(channel) {
+                *k = slot.map(|m| (m.capacity, m.ranks));
+            }
+
+            key
+        };
+
+        let mut reference = None;
+        let mut asymmetric = 0;
+
+        for (index, channel) in slots.chunks(slots_per_channel).enumerate() {
+            if channel.iter().all(Option::is_none) {
+                continue;
+            }
+
+            let key = population(channel);
+
+            match reference {
+                None => reference = Some(key),
+                Some(reference) if reference != key => asymmetric |= 1 << index,
+                Some(_) => {}
+            }
+        }
+
+        let mixed_device_types = differs(|m| m.device_type as u64);
+
+        Self {
+            populated: modules().count() as u8,
+            device_type: match first {
+                Some(first) if !mixed_device_types => Some(first.device_type),
+                _ => None,
+            },
+            capacity: modules().map(|m| m.capacity).sum(),
+            tck_min: modules().map(|m| m.tck_min).max(),
+            mixed_device_types,
+            mixed_vendors: differs(|m| m.module_manufacturer as u64),
+            mixed_ranks: differs(|m| m.ranks.map_or(0, u64::from)),
+            asymmetric,
+        }
+    }
+
+    ///
+    /// The highest data rate, in MT/s, that all modules support.
+    ///
+    pub fn data_rate(&self) -> Option<u32> {
+        match self.tck_min? {
+            Picoseconds(0) => None,
+            Picoseconds(tck) => Some(2_000_000 / tck),
+        }
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = InventoryWarning> + '_ {
+        let flag = |set, warning| if set { Some(warning) } else { None };
+
+        flag(self.mixed_device_types, InventoryWarning::MixedDeviceTypes)
+            .into_iter()
+            .chain(flag(self.mixed_vendors, InventoryWarning::MixedVendors))
+            .chain(flag(self.mixed_ranks, InventoryWarning::MixedRanks))
+            .chain(
+                (0..MAX_DEVICES)
+                    .filter(move |&c| self.asymmetric & (1 << c) != 0)
+                    .map(InventoryWarning::AsymmetricChannel),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{update_crc, MAX_SIZE};
+
+    fn module(tck: u8, ranks: u8, vendor: u8) -> Option<Summary> {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x004] = 0x85;
+        buf[0x00c] = (ranks - 1) << 3;
+        buf[0x00d] = 0x0b;
+        buf[0x012] = tck;
+        buf[0x140] = 0x80;
+        buf[0x141] = vendor;
+        update_crc(&mut buf);
+        Summary::from_spd(&buf)
+    }
+
+    #[test]
+    fn uniform() {
+        let m = module(0x05, 2, 0xce);
+        let slots = [m, None, m, None];
+        let inventory = Inventory::new(&slots, 2);
+
+        assert_eq!(inventory.populated, 2);
+        assert_eq!(inventory.capacity, Some(64 << 30));
+        assert_eq!(inventory.data_rate(), Some(3200));
+        assert_eq!(inventory.device_type, Some(DeviceType::Ddr4));
+        assert_eq!(inventory.warnings().next(), None);
+
+        let inventory = Inventory::new(&[None; 4], 2);
+        assert_eq!(inventory.populated, 0);
+        assert_eq!(inventory.capacity, Some(0));
+        assert_eq!(inventory.data_rate(), None);
+        assert_eq!(inventory.warnings().next(), None);
+    }
+
+    #[test]
+    fn mixed() {
+        let slots = [
+            module(0x05, 2, 0xce),
+            module(0x06, 1, 0x2c),
+            None,
+            None,
+            module(0x05, 2, 0xce),
+            None,
+        ];
+        let inventory = Inventory::new(&slots, 2);
+
+        //
+        // DDR4-2666 (tCK of 750 ps) is the fastest common rate.
+        //
+        assert_eq!(inventory.data_rate(), Some(2666));
+        assert_eq!(inventory.capacity, Some(80 << 30));
+
+        let mut warnings = inventory.warnings();
+        assert_eq!(warnings.next(), Some(InventoryWarning::MixedVendors));
+        assert_eq!(warnings.next(), Some(InventoryWarning::MixedRanks));
+        assert_eq!(
+            warnings.next(),
+            Some(InventoryWarning::AsymmetricChannel(2))
+        );
+        assert_eq!(warnings.next(), None);
+    }
+}
@@ -0,0 +1,66 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+///
+/// The manufacturers this crate can name, as (number of continuation codes,
+/// identification code), both without their parity bits.  This covers the
+/// common DRAM and SPD hub vendors, not the whole of JEP106.
+///
+const MANUFACTURERS: &[(u8, u8, &str)] = &[
+    (0, 0x2c, "Micron Technology"),
+    (0, 0x2d, "SK hynix"),
+    (0, 0x33, "Renesas (IDT)"),
+    (0, 0x4e, "Samsung"),
+    (6, 0x32, "Montage Technology"),
+];
+
+///
+/// The name of a manufacturer, given its JEP106 ID as stored in SPD:  the
+/// continuation count in the low byte and the identification code in the
+/// high byte, each with an odd parity bit.  Returns `None` if the
+/// manufacturer is not known.
+///
+pub fn manufacturer_name(id: u16) -> Option<&'static str> {
+    let [bank, code] = id.to_le_bytes();
+
+    MANUFACTURERS
+        .iter()
+        .find(|&&(b, c, _)| b == bank & 0x7f && c == code & 0x7f)
+        .map(|&(_, _, name)| name)
+}
+
+///
+/// Returns true if the ID is well formed:  both bytes have odd parity, and
+/// the identification code is neither zero nor the continuation code.
+///
+pub fn is_valid_jep106(id: u16) -> bool {
+    let [bank, code] = id.to_le_bytes();
+
+    bank.count_ones() % 2 == 1
+        && code.count_ones() % 2 == 1
+        && code & 0x7f != 0
+        && code & 0x7f != 0x7f
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        assert_eq!(manufacturer_name(0xce80), Some("Samsung"));
+        assert_eq!(manufacturer_name(0x2c80), Some("Micron Technology"));
+        assert_eq!(manufacturer_name(0x3286), Some("Montage Technology"));
+        assert_eq!(manufacturer_name(0xb380), Some("Renesas (IDT)"));
+        assert_eq!(manufacturer_name(0xce01), None);
+
+        assert!(is_valid_jep106(0xce80));
+        assert!(is_valid_jep106(0x3286));
+        assert!(!is_valid_jep106(0x4e80));
+        assert!(!is_valid_jep106(0x0000));
+        assert!(!is_valid_jep106(0x7f80));
+    }
+}
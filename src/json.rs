@@ -0,0 +1,100 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+//!
+//! Export of a decoded [`Summary`] as JSON via `serde-json-core`, requiring
+//! neither `std` nor an allocator.
+//!
+
+use crate::{DeviceType, Error, Summary};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct SummaryJson<'a> {
+    device_type: &'static str,
+    module_type: u8,
+    module_manufacturer: u16,
+    dram_manufacturer: u16,
+    serial: u32,
+    year: u8,
+    week: u8,
+    part_number: &'a str,
+    tck_min_ps: u32,
+    taa_min_ps: u32,
+    capacity: Option<u64>,
+    crc_valid: Option<bool>,
+}
+
+impl<'a> From<&'a Summary> for SummaryJson<'a> {
+    fn from(s: &'a Summary) -> Self {
+        Self {
+            device_type: match s.device_type {
+                DeviceType::Ddr4 => "DDR4",
+                DeviceType::Ddr4E => "DDR4E",
+                DeviceType::Ddr5 => "DDR5",
+            },
+            module_type: s.module_type,
+            module_manufacturer: s.module_manufacturer,
+            dram_manufacturer: s.dram_manufacturer,
+            serial: s.serial,
+            year: s.year,
+            week: s.week,
+            part_number: core::str::from_utf8(s.part_number()).unwrap_or(""),
+            tck_min_ps: s.tck_min.0,
+            taa_min_ps: s.taa_min.0,
+            capacity: s.capacity,
+            crc_valid: s.crc_valid,
+        }
+    }
+}
+
+impl Serialize for Summary {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        SummaryJson::from(self).serialize(serializer)
+    }
+}
+
+///
+/// Serialize the summary as JSON into `buf`, returning the number of bytes
+/// written.
+///
+pub fn to_json(summary: &Summary, buf: &mut [u8]) -> Result<usize, Error> {
+    serde_json_core::to_slice(summary, buf).map_err(|_| Error::BufferTooSmall)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    #[test]
+    fn json() {
+        let mut image = [0u8; MAX_SIZE];
+        image[0x002] = 0x0c;
+        image[0x012] = 0x05;
+        image[0x149..0x15d].copy_from_slice(b"ABC                 ");
+
+        let summary = Summary::from_spd(&image).unwrap();
+        let mut buf = [0u8; 512];
+        let len = to_json(&summary, &mut buf).unwrap();
+
+        assert_eq!(
+            core::str::from_utf8(&buf[..len]).unwrap(),
+            "{\"device_type\":\"DDR4\",\"module_type\":0,\
+            \"module_manufacturer\":0,\"dram_manufacturer\":0,\"serial\":0,\
+            \"year\":0,\"week\":0,\"part_number\":\"ABC\",\"tck_min_ps\":625,\
+            \"taa_min_ps\":0,\"capacity\":67108864,\"crc_valid\":false}"
+        );
+
+        assert_eq!(
+            to_json(&summary, &mut buf[..16]),
+            Err(Error::BufferTooSmall)
+        );
+    }
+}
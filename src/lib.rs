@@ -11,12 +11,129 @@
 pub use num_derive::{FromPrimitive, ToPrimitive};
 pub use num_traits::{FromPrimitive, ToPrimitive};
 
-type SelectAddress = u8;
+mod area;
+mod bus;
+mod bytemap;
+mod compact;
+mod coverage;
+mod crc;
+pub mod ddr5;
+mod derate;
+mod device;
+mod dump;
+mod emulator;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod field;
+mod fingerprint;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod generic;
+mod golden;
+#[cfg(feature = "hex")]
+pub mod hex;
+mod image;
+mod inventory;
+mod jep106;
+#[cfg(feature = "json")]
+pub mod json;
+mod mock;
+mod module;
+mod organization;
+mod paging;
+mod parser;
+mod plan;
+mod protection;
+mod region;
+mod relabel;
+mod repair;
+#[cfg(feature = "smbios")]
+pub mod smbios;
+mod strictness;
+mod summary;
+mod thermal;
+mod timing;
+mod topology;
+pub mod tse2004;
+mod validate;
+
+pub use area::{
+    end_user_area, end_user_area_mut, manufacturer_area, manufacturer_area_mut,
+    Area, AREA_SIZE,
+};
+pub use bus::{execute, SpdBus};
+pub use bytemap::{ByteClass, ByteMap, DDR4_BYTE_MAP, DDR5_BYTE_MAP};
+pub use compact::{CompactSpd, COMPACT_VERSION};
+pub use coverage::{ImageCoverage, PartialImage};
+pub use crc::{crc16, crc_blocks, update_crc, verify_crc, CrcBlock};
+pub use derate::{ClockLimits, DerateError, OperatingPoint, Parameter};
+pub use device::{DeviceError, PagedDevice, SpdDevice};
+pub use dump::{
+    dump, restore, Delay, TransferError, TransferReport, WRITE_CYCLE_MS,
+};
+pub use emulator::{
+    Protocol, SpdEmulator, DDR5_MAX_SIZE, DDR5_PAGE_SIZE, WRITE_PAGE_SIZE,
+};
+pub use field::{Field, Value};
+pub use fingerprint::{content_hash, fingerprint, per_unit_range};
+pub use generic::GenericView;
+pub use golden::{golden_violations, matches_golden, GoldenPolicy, Violation};
+pub use image::{FlushPlan, SpdImage};
+pub use inventory::{Inventory, InventoryWarning};
+pub use jep106::{is_valid_jep106, manufacturer_name};
+pub use mock::{MockSpdDevice, Nack};
+pub use module::{
+    FunctionClass, FunctionInterface, HybridMedia, Mechanical, MillimeterRange,
+    RawCard, RawCardDesign,
+};
+pub use organization::Organization;
+pub use paging::{Ee1004Pages, PageSelector, SelectWrite, Spd5118Pages};
+pub use parser::{Available, StreamingParser};
+pub use plan::{get_field, set_field, Operation, Plan, Planner};
+pub use protection::{
+    AuditError, Ee1004Change, ProtectionChange, ProtectionState,
+};
+pub use region::{classify, regions, Region};
+pub use relabel::{reserialize, Relabel};
+pub use repair::{MaximumActivateCount, PostPackageRepair, RepairCapabilities};
+pub use strictness::{
+    DecodeError, Decoded, SalvagedIdentity, Strictness, Warnings,
+};
+pub use summary::{ModuleIdentity, Summary, PART_NUMBER_LEN};
+pub use thermal::{
+    Recommendation, RefreshRate, ThermalPolicy, DOUBLE_REFRESH_THRESHOLD,
+    EXTENDED_TEMPERATURE_LIMIT,
+};
+pub use timing::{
+    clocks, decode_timing, encode_timing, CasLatencies, Picoseconds, Timing,
+    FTB_PS, MTB_PS,
+};
+pub use topology::{
+    Anomaly, DeviceClass, LocalDevice, PopulationMap, Slot, Topology,
+};
+pub use validate::{assert_valid_spd, validate_spd, ValidationError};
+
 type Block = u8;
 
 pub const MAX_DEVICES: u8 = 8;
 pub const MAX_SIZE: usize = 512;
 pub const PAGE_SIZE: usize = 256;
+pub const BLOCK_SIZE: usize = 128;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Error {
+    /// A value of the wrong type was supplied for a field.
+    TypeMismatch,
+    /// A value is too long to fit in its field.
+    ValueTooLong,
+    /// The supplied buffer is too small.
+    BufferTooSmall,
+    /// A serialized representation is of a version this crate does not
+    /// understand.
+    UnsupportedVersion,
+    /// The offset lies in a part of the image that has not been read.
+    OffsetNotCovered,
+}
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Page(pub u8);
@@ -31,6 +148,88 @@ impl Page {
     }
 }
 
+///
+/// The select address to which a device is strapped:  one of the
+/// [`MAX_DEVICES`] addresses distinguishing devices of the same type on a
+/// bus.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SelectAddress(u8);
+
+impl SelectAddress {
+    ///
+    /// Construct a select address, panicking (at compile time, in a const
+    /// context) if it is out of range.
+    ///
+    pub const fn new(sa: u8) -> Self {
+        assert!(sa < MAX_DEVICES, "select address out of range");
+        Self(sa)
+    }
+
+    ///
+    /// Construct a select address, returning `None` if it is out of range.
+    ///
+    pub const fn try_new(sa: u8) -> Option<Self> {
+        if sa < MAX_DEVICES {
+            Some(Self(sa))
+        } else {
+            None
+        }
+    }
+
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+
+    ///
+    /// Iterate over all select addresses in order.
+    ///
+    pub fn all() -> impl Iterator<Item = SelectAddress> {
+        (0..MAX_DEVICES).map(Self)
+    }
+}
+
+///
+/// A 7-bit I2C/SMBus address.  Stacks disagree on whether addresses are
+/// expressed in 7 bits or shifted left with the read/write bit in bit 0; this
+/// type holds the former and converts explicitly to either form.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BusAddress(u8);
+
+impl BusAddress {
+    ///
+    /// Construct from a 7-bit address, returning `None` if it is out of
+    /// range.
+    ///
+    pub const fn new(addr: u8) -> Option<Self> {
+        if addr <= 0x7f {
+            Some(Self(addr))
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Construct from an 8-bit address, ignoring the read/write bit.
+    ///
+    pub const fn from_8bit(addr: u8) -> Self {
+        Self(addr >> 1)
+    }
+
+    pub const fn as_7bit(self) -> u8 {
+        self.0
+    }
+
+    pub const fn as_8bit_write(self) -> u8 {
+        self.0 << 1
+    }
+
+    pub const fn as_8bit_read(self) -> u8 {
+        (self.0 << 1) | 1
+    }
+}
+
 ///
 /// Functions as described in Table 2 of EE1004.
 ///
@@ -43,8 +242,66 @@ pub enum Function {
     PageAddress(Page),
 }
 
-#[derive(Copy, Clone, Debug, FromPrimitive)]
-pub enum Offset {
+///
+/// Define an offset enum along with the table of all of its variants and
+/// their names, so that tooling can walk every known field without keeping
+/// its own copy of the list.  Multi-byte fields named by their first and
+/// last bytes are declared as `base..=limit` ranges.
+///
+macro_rules! offsets {
+    ($offset:ident { $($name:ident = $value:expr,)* }
+        ranges { $($base:ident..=$limit:ident,)* }) => {
+        #[derive(Copy, Clone, Debug, PartialEq, FromPrimitive)]
+        pub enum $offset {
+            $($name = $value,)*
+        }
+
+        impl $offset {
+            ///
+            /// Every defined offset, in ascending order.
+            ///
+            pub const ALL: &'static [$offset] = &[$($offset::$name,)*];
+
+            ///
+            /// Every field spanning a range of bytes, as (base, limit).
+            ///
+            pub const RANGES: &'static [($offset, $offset)] =
+                &[$(($offset::$base, $offset::$limit),)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($offset::$name => stringify!($name),)*
+                }
+            }
+
+            ///
+            /// The field that owns the byte at the given index, along with
+            /// the index of the byte within it.  A range of bytes is owned
+            /// by its base.
+            ///
+            pub fn containing(index: usize) -> Option<($offset, usize)> {
+                let range = Self::RANGES.iter().find(|&&(base, limit)| {
+                    (base as usize..=limit as usize).contains(&index)
+                });
+
+                match range {
+                    Some(&(base, _)) => Some((base, index - base as usize)),
+                    None => Self::from_usize(index).map(|o| (o, 0)),
+                }
+            }
+        }
+
+        impl From<$offset> for usize {
+            fn from(offset: $offset) -> usize {
+                offset as usize
+            }
+        }
+    };
+}
+
+pub(crate) use offsets;
+
+offsets! { Offset {
     SPDDeviceSize = 0x000,
     SPDRevision = 0x001,
     DRAMDeviceType = 0x002,
@@ -58,6 +315,7 @@ pub enum Offset {
     ModuleNominalVoltage = 0x00b,
     ModuleOrganization = 0x00c,
     ModuleMemoryBusWidth = 0x00d,
+    ModuleThermalSensor = 0x00e,
     ExtendedModuleType = 0x00f,
     Timebases = 0x011,
     TCkAvgMin = 0x012,
@@ -71,7 +329,6 @@ pub enum Offset {
     TRPMin = 0x01a,
     UpperNibblesTRASMin = 0x01b,
     TRASMin = 0x01c,
-
     TRCMin = 0x01d,
     TRFC1MinLSB = 0x01e,
     TRFC1MinMSB = 0x01f,
@@ -83,6 +340,7 @@ pub enum Offset {
     TFAWminLSB = 0x025,
     TRRDSMin = 0x026,
     TRRDLMin = 0x027,
+    TCCDLMin = 0x028,
     UpperNibbleTWRMin = 0x29,
     TWRMin = 0x2a,
     UpperNibblesTWTRMin = 0x2b,
@@ -99,6 +357,13 @@ pub enum Offset {
     TCkAvgMinFine = 0x7d,
     CRCBaseLSB = 0x7e,
     CRCBaseMSB = 0x7f,
+    ModuleNominalHeight = 0x080,
+    ModuleMaximumThickness = 0x081,
+    ReferenceRawCard = 0x082,
+    FunctionInterfaceBase = 0x0cc,
+    FunctionInterfaceLimit = 0x0db,
+    CRCModuleSpecificLSB = 0x0fe,
+    CRCModuleSpecificMSB = 0x0ff,
     ModuleManufacturerIDCodeLSB = 0x140,
     ModuleManufacturerIDCodeMSB = 0x141,
     ModuleManufacturingLocation = 0x142,
@@ -110,10 +375,14 @@ pub enum Offset {
     ModuleSerialNumber3 = 0x148,
     PartNumberBase = 0x149,
     PartNumberLimit = 0x15c,
+    ModuleRevisionCode = 0x15d,
     DRAMManufacturerIDCodeLSB = 0x15e,
     DRAMManufacturerIDCodeMSB = 0x15f,
     DRAMStepping = 0x160,
-}
+} ranges {
+    FunctionInterfaceBase..=FunctionInterfaceLimit,
+    PartNumberBase..=PartNumberLimit,
+}}
 
 impl Offset {
     pub fn to_usize(self) -> usize {
@@ -123,30 +392,141 @@ impl Offset {
     pub fn within(self, buf: &[u8]) -> u8 {
         buf[self as usize]
     }
+
+    pub fn iter() -> impl Iterator<Item = Offset> {
+        Self::ALL.iter().copied()
+    }
+}
+
+///
+/// An offset not (yet) named by [`Offset`] or [`ddr5::Offset`].  This can be
+/// used anywhere an offset is accepted as `impl Into<usize>`, undergoing the
+/// same page translation and bounds checks.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RawOffset(pub usize);
+
+impl RawOffset {
+    pub fn to_usize(self) -> usize {
+        self.0
+    }
+
+    pub fn within(self, buf: &[u8]) -> u8 {
+        buf[self.0]
+    }
+
+    ///
+    /// The byte at this offset, or `None` if it is beyond the end of `buf`.
+    ///
+    pub fn get(self, buf: &[u8]) -> Option<u8> {
+        buf.get(self.0).copied()
+    }
+}
+
+impl From<RawOffset> for usize {
+    fn from(offset: RawOffset) -> usize {
+        offset.0
+    }
+}
+
+impl From<Offset> for RawOffset {
+    fn from(offset: Offset) -> RawOffset {
+        RawOffset(offset.into())
+    }
+}
+
+impl From<ddr5::Offset> for RawOffset {
+    fn from(offset: ddr5::Offset) -> RawOffset {
+        RawOffset(offset.into())
+    }
 }
 
+///
+/// DRAM device types, as encoded in byte 2 (the key byte) of the SPD.
+///
+#[derive(Copy, Clone, Debug, PartialEq, FromPrimitive)]
+pub enum DeviceType {
+    Ddr4 = 0x0c,
+    /// DDR4E, whose SPD has the DDR4 layout.
+    Ddr4E = 0x0e,
+    Ddr5 = 0x12,
+}
+
+impl DeviceType {
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        Self::from_u8(*buf.get(Offset::DRAMDeviceType.to_usize())?)
+    }
+
+    ///
+    /// The device type whose SPD layout this device type shares.
+    ///
+    pub fn layout(self) -> DeviceType {
+        match self {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => DeviceType::Ddr4,
+            DeviceType::Ddr5 => DeviceType::Ddr5,
+        }
+    }
+}
+
+///
+/// The key byte of an image, which may denote a device type that this crate
+/// does not (yet) recognize.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KeyByte {
+    Known(DeviceType),
+    Unknown(u8),
+}
+
+impl KeyByte {
+    ///
+    /// Returns `None` only if the image is too short to have a key byte.
+    ///
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        let key = *buf.get(Offset::DRAMDeviceType.to_usize())?;
+        Some(
+            DeviceType::from_u8(key)
+                .map_or(KeyByte::Unknown(key), KeyByte::Known),
+        )
+    }
+
+    pub fn device_type(self) -> Option<DeviceType> {
+        match self {
+            KeyByte::Known(device_type) => Some(device_type),
+            KeyByte::Unknown(_) => None,
+        }
+    }
+}
+
+///
+/// The number of 7-bit device codes.
+///
+const NDEVICE_CODES: usize = 128;
+
+const DEVICE_CODES: [Option<Function>; NDEVICE_CODES] = {
+    let mut table = [None; NDEVICE_CODES];
+    let mut code = 0;
+
+    while code < NDEVICE_CODES {
+        table[code] = Function::decode_device_code(code as u8);
+        code += 1;
+    }
+
+    table
+};
+
 impl Function {
     ///
     /// For a given function, return its device code.  This code follows the
-    /// structure of Table 2 in EE1004.
+    /// structure of Table 2 in EE1004:  a 4-bit device type identifier and a
+    /// 3-bit select address, grouped accordingly.
     ///
+    #[allow(clippy::unusual_byte_groupings)]
     pub fn to_device_code(self) -> Option<u8> {
         match self {
-            Function::Temperature(addr) => {
-                if addr <= 0b111 {
-                    Some((0b0011 << 3) | addr)
-                } else {
-                    None
-                }
-            }
+            Function::Temperature(addr) => Some((0b0011 << 3) | addr.0),
 
-            Function::Memory(addr) => {
-                if addr <= 0b111 {
-                    Some((0b1010 << 3) | addr)
-                } else {
-                    None
-                }
-            }
+            Function::Memory(addr) => Some((0b1010 << 3) | addr.0),
 
             Function::ProtectionStatus(block) => {
                 let dtid = 0b0110 << 3;
@@ -155,7 +535,7 @@ impl Function {
                     0 => Some(dtid | 0b001),
                     1 => Some(dtid | 0b100),
                     2 => Some(dtid | 0b101),
-                    3 => Some(dtid | 0b000),
+                    3 => Some(dtid),
                     _ => None,
                 }
             }
@@ -174,19 +554,60 @@ impl Function {
         }
     }
 
+    ///
+    /// For a given function, return its device code as a bus address.
+    ///
+    pub fn to_bus_address(self) -> Option<BusAddress> {
+        self.to_device_code().and_then(BusAddress::new)
+    }
+
+    ///
+    /// For a given bus address, return the function (if any).
+    ///
+    pub fn from_bus_address(addr: BusAddress) -> Option<Self> {
+        Self::from_device_code(addr.as_7bit())
+    }
+
+    ///
+    /// For a given device code, return the function (if any).  This is a
+    /// lookup in a table generated at compile time by [`decode_device_code`],
+    /// suitable for use in bus scan loops.
+    ///
+    /// [`decode_device_code`]: Function::decode_device_code
+    ///
+    pub fn from_device_code(code: u8) -> Option<Self> {
+        match DEVICE_CODES.get(code as usize) {
+            Some(func) => *func,
+            None => None,
+        }
+    }
+
+    ///
+    /// Iterate over every valid device code and its function, in device code
+    /// order.
+    ///
+    pub fn device_codes() -> impl Iterator<Item = (u8, Function)> {
+        DEVICE_CODES
+            .iter()
+            .enumerate()
+            .filter_map(|(code, func)| func.map(|func| (code as u8, func)))
+    }
+
     ///
     /// For a given device code, return the function (if any).  This code follows
     /// the structure of Table 2 in EE1004, which yes, is in function order
     /// not device code order.
     ///
-    pub fn from_device_code(code: u8) -> Option<Self> {
+    pub const fn decode_device_code(code: u8) -> Option<Self> {
         let device_type_identifier = code >> 3;
         let select_address = code & 0b111;
 
         match device_type_identifier {
-            0b0011 => Some(Function::Temperature(select_address)),
+            0b0011 => {
+                Some(Function::Temperature(SelectAddress(select_address)))
+            }
 
-            0b1010 => Some(Function::Memory(select_address)),
+            0b1010 => Some(Function::Memory(SelectAddress(select_address))),
 
             0b0110 => match select_address {
                 0b001 => Some(Function::ProtectionStatus(0)),
@@ -210,13 +631,24 @@ mod tests {
     use std::println;
 
     #[test]
+    #[allow(clippy::unusual_byte_groupings)]
     fn table() {
-        assert_eq!(Function::Temperature(0).to_device_code(), Some(0b0011_000));
-        assert_eq!(Function::Temperature(1).to_device_code(), Some(0b0011_001));
-        assert_eq!(Function::Temperature(9).to_device_code(), None);
-        assert_eq!(Function::Memory(0).to_device_code(), Some(0b1010_000));
-        assert_eq!(Function::Memory(1).to_device_code(), Some(0b1010_001));
-        assert_eq!(Function::Memory(9).to_device_code(), None);
+        const SA0: SelectAddress = SelectAddress::new(0);
+        const SA1: SelectAddress = SelectAddress::new(1);
+
+        assert_eq!(
+            Function::Temperature(SA0).to_device_code(),
+            Some(0b0011_000)
+        );
+        assert_eq!(
+            Function::Temperature(SA1).to_device_code(),
+            Some(0b0011_001)
+        );
+        assert_eq!(Function::Memory(SA0).to_device_code(), Some(0b1010_000));
+        assert_eq!(Function::Memory(SA1).to_device_code(), Some(0b1010_001));
+        assert_eq!(SelectAddress::try_new(7), Some(SelectAddress::new(7)));
+        assert_eq!(SelectAddress::try_new(9), None);
+        assert_eq!(SelectAddress::all().count(), MAX_DEVICES as usize);
     }
 
     #[test]
@@ -228,4 +660,116 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(Offset::iter().next().unwrap().name(), "SPDDeviceSize");
+        assert_eq!(Offset::DRAMStepping.name(), "DRAMStepping");
+
+        for pair in Offset::ALL.windows(2) {
+            assert!(pair[0].to_usize() < pair[1].to_usize());
+        }
+
+        for offset in Offset::iter() {
+            let from = Offset::from_usize(offset.to_usize()).unwrap();
+            assert_eq!(from.name(), offset.name());
+        }
+    }
+
+    #[test]
+    fn containing() {
+        assert_eq!(Offset::containing(0), Some((Offset::SPDDeviceSize, 0)));
+        assert_eq!(Offset::containing(0x006), None);
+        assert_eq!(Offset::containing(0x02e), None);
+        assert_eq!(
+            Offset::containing(0x14a),
+            Some((Offset::PartNumberBase, 1))
+        );
+        assert_eq!(
+            Offset::containing(0x15c),
+            Some((Offset::PartNumberBase, 19))
+        );
+        assert_eq!(
+            Offset::containing(0x15d),
+            Some((Offset::ModuleRevisionCode, 0))
+        );
+        assert_eq!(
+            Offset::containing(0x0d0),
+            Some((Offset::FunctionInterfaceBase, 4))
+        );
+        assert_eq!(Offset::containing(0x161), None);
+
+        assert_eq!(
+            ddr5::Offset::containing(0x226),
+            Some((ddr5::Offset::PartNumberBase, 29))
+        );
+        assert_eq!(ddr5::Offset::containing(0x3ff), None);
+
+        //
+        // No named offset falls strictly within a range.
+        //
+        for &(base, limit) in Offset::RANGES {
+            assert!(base.to_usize() < limit.to_usize());
+            assert!(Offset::iter().all(|o| o == base
+                || o == limit
+                || !(base.to_usize()..=limit.to_usize())
+                    .contains(&o.to_usize())));
+        }
+    }
+
+    #[test]
+    fn table_matches_decode() {
+        for i in 0..=0xff {
+            assert_eq!(
+                Function::from_device_code(i),
+                Function::decode_device_code(i)
+            );
+        }
+
+        assert_eq!(Function::device_codes().count(), 23);
+
+        for (code, func) in Function::device_codes() {
+            assert_eq!(func.to_device_code(), Some(code));
+        }
+    }
+
+    #[test]
+    fn bus_address() {
+        let sa = SelectAddress::new(2);
+        let addr = Function::Memory(sa).to_bus_address().unwrap();
+        assert_eq!(addr.as_7bit(), 0x52);
+        assert_eq!(addr.as_8bit_write(), 0xa4);
+        assert_eq!(addr.as_8bit_read(), 0xa5);
+        assert_eq!(BusAddress::from_8bit(0xa5), addr);
+        assert_eq!(
+            Function::from_bus_address(addr),
+            Some(Function::Memory(sa))
+        );
+
+        assert_eq!(BusAddress::new(0x80), None);
+    }
+
+    #[test]
+    fn raw_offset() {
+        let raw = RawOffset(0x006);
+        assert_eq!(get_field(&Ee1004Pages, raw), Some((Page(0), 0x06)));
+        assert_eq!(
+            get_field(&Ee1004Pages, RawOffset(0x1ff)),
+            Some((Page(1), 0xff))
+        );
+        assert_eq!(get_field(&Ee1004Pages, RawOffset(0x200)), None);
+
+        let sa = SelectAddress::new(0);
+        assert_eq!(
+            get_field(&Spd5118Pages(sa), RawOffset(0x228)),
+            Some((Page(4), 0xa8))
+        );
+
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x006] = 0x5a;
+        assert_eq!(raw.within(&buf), 0x5a);
+        assert_eq!(RawOffset(0x200).get(&buf), None);
+        assert_eq!(RawOffset::from(Offset::TAAMin), RawOffset(0x018));
+        assert_eq!(usize::from(RawOffset::from(ddr5::Offset::CRCLSB)), 0x1fe);
+    }
 }
@@ -0,0 +1,151 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{SelectAddress, SpdBus, SpdEmulator, DDR5_MAX_SIZE, MAX_SIZE};
+
+///
+/// A transaction was not acknowledged by the device code it addressed.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Nack {
+    pub code: u8,
+}
+
+///
+/// An in-memory SPD device implementing [`SpdBus`], so that code written
+/// against the bus trait can be tested on the host.  Paging and write
+/// protection behave as on real parts (by way of [`SpdEmulator`]), and the
+/// transactions issued are counted so that tests can assert on bus traffic.
+///
+pub struct MockSpdDevice {
+    emulator: SpdEmulator,
+    writes: usize,
+    reads: usize,
+}
+
+impl MockSpdDevice {
+    ///
+    /// A DDR4 (EE1004) device at the given select address.
+    ///
+    pub fn ddr4(sa: SelectAddress, image: &[u8; MAX_SIZE]) -> Self {
+        Self::from_emulator(SpdEmulator::ee1004(sa, image))
+    }
+
+    ///
+    /// A DDR5 (SPD5118) device at the given select address.
+    ///
+    pub fn ddr5(sa: SelectAddress, image: &[u8; DDR5_MAX_SIZE]) -> Self {
+        Self::from_emulator(SpdEmulator::spd5118(sa, image))
+    }
+
+    pub fn from_emulator(emulator: SpdEmulator) -> Self {
+        Self {
+            emulator,
+            writes: 0,
+            reads: 0,
+        }
+    }
+
+    pub fn image(&self) -> &[u8] {
+        self.emulator.image()
+    }
+
+    ///
+    /// The underlying emulator, to inspect or alter protection and paging
+    /// state.
+    ///
+    pub fn emulator(&mut self) -> &mut SpdEmulator {
+        &mut self.emulator
+    }
+
+    ///
+    /// The number of write and read transactions issued, respectively.
+    ///
+    pub fn transactions(&self) -> (usize, usize) {
+        (self.writes, self.reads)
+    }
+}
+
+impl SpdBus for MockSpdDevice {
+    type Error = Nack;
+
+    fn write(&mut self, code: u8, bytes: &[u8]) -> Result<(), Nack> {
+        self.writes += 1;
+
+        if self.emulator.on_write(code, bytes) {
+            Ok(())
+        } else {
+            Err(Nack { code })
+        }
+    }
+
+    fn read(&mut self, code: u8, buf: &mut [u8]) -> Result<(), Nack> {
+        self.reads += 1;
+
+        if self.emulator.on_read(code, buf) {
+            Ok(())
+        } else {
+            Err(Nack { code })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ddr5, execute, Ee1004Pages, Function, Offset, Planner, Spd5118Pages,
+    };
+
+    #[test]
+    fn ddr4() {
+        let mut image = [0u8; MAX_SIZE];
+        image[0x0fe] = 0x12;
+        image[0x100] = 0x34;
+
+        let sa = SelectAddress::new(3);
+        let code = Function::Memory(sa).to_device_code().unwrap();
+        let mut dev = MockSpdDevice::ddr4(sa, &image);
+        let mut planner = Planner::new(Ee1004Pages, code);
+
+        let mut buf = [0u8; 4];
+        assert_eq!(execute(&mut dev, planner.read(0xfe, 4), &mut buf), Ok(4));
+        assert_eq!(buf, [0x12, 0, 0x34, 0]);
+        assert_eq!(dev.transactions(), (4, 2));
+
+        //
+        // With block 2 protected, the write is NACK'd and the image is
+        // unchanged.
+        //
+        let swp2 = Function::ProtectionStatus(2).to_device_code().unwrap();
+        dev.write(swp2, &[0, 0]).unwrap();
+
+        let location = Offset::ModuleManufacturingLocation;
+        assert_eq!(
+            execute(&mut dev, planner.set_field(location, &7), &mut []),
+            Err(Nack { code })
+        );
+        assert_eq!(dev.image()[0x142], 0);
+
+        assert_eq!(dev.read(0x7f, &mut buf), Err(Nack { code: 0x7f }));
+    }
+
+    #[test]
+    fn ddr5() {
+        let sa = SelectAddress::new(0);
+        let code = Function::Memory(sa).to_device_code().unwrap();
+        let mut dev = MockSpdDevice::ddr5(sa, &[0; DDR5_MAX_SIZE]);
+        let mut planner = Planner::new(Spd5118Pages(sa), code);
+
+        let stepping = ddr5::Offset::DRAMStepping;
+        execute(&mut dev, planner.set_field(stepping, &0x42), &mut []).unwrap();
+
+        let mut buf = [0u8];
+        execute(&mut dev, planner.get_field(stepping), &mut buf).unwrap();
+        assert_eq!(buf[0], 0x42);
+        assert_eq!(dev.emulator().page(), 4);
+    }
+}
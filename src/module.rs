@@ -0,0 +1,403 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{DeviceType, Offset};
+use core::fmt;
+
+///
+/// The reference design encoded in the reference raw card byte.  Designs are
+/// lettered A through Z, then AA, AB and so on; a design of ZZ indicates that
+/// no JEDEC reference raw card was used.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RawCardDesign {
+    Reference(u8),
+    NotReference,
+}
+
+impl fmt::Display for RawCardDesign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RawCardDesign::Reference(index) if index < 26 => {
+                write!(f, "{}", (b'A' + index) as char)
+            }
+            RawCardDesign::Reference(index) => {
+                let index = index - 26;
+                write!(
+                    f,
+                    "{}{}",
+                    (b'A' + index / 26) as char,
+                    (b'A' + index % 26) as char
+                )
+            }
+            RawCardDesign::NotReference => write!(f, "ZZ"),
+        }
+    }
+}
+
+///
+/// The reference raw card (design and revision) a module was built from, as
+/// described in byte 130 (and the raw card extension in byte 128) of the
+/// DDR4 SPD.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RawCard {
+    pub design: RawCardDesign,
+    pub revision: u8,
+}
+
+impl RawCard {
+    pub fn from_spd(buf: &[u8]) -> Self {
+        Self::decode(
+            Offset::ReferenceRawCard.within(buf),
+            Offset::ModuleNominalHeight.within(buf),
+        )
+    }
+
+    ///
+    /// Decode from the reference raw card byte and the raw card extension
+    /// byte.  Bit 7 of the reference raw card byte selects the second bank of
+    /// 31 designs (AF onward).  A revision field of 3 means "revision 3 or
+    /// later", with the actual revision given by adding the 3-bit raw card
+    /// extension in the upper bits of byte 128.
+    ///
+    pub fn decode(reference: u8, extension: u8) -> Self {
+        let index = reference & 0b1_1111;
+
+        let design = if index == 0b1_1111 {
+            RawCardDesign::NotReference
+        } else if reference & 0b1000_0000 != 0 {
+            RawCardDesign::Reference(index + 0b1_1111)
+        } else {
+            RawCardDesign::Reference(index)
+        };
+
+        let revision = match (reference >> 5) & 0b11 {
+            0b11 => 3 + (extension >> 5),
+            revision => revision,
+        };
+
+        Self { design, revision }
+    }
+}
+
+impl fmt::Display for RawCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} rev {}", self.design, self.revision)
+    }
+}
+
+///
+/// A range of millimeters as encoded in the mechanical bytes:  greater than
+/// `above` and at most `at_most` (if bounded).
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MillimeterRange {
+    pub above: u8,
+    pub at_most: Option<u8>,
+}
+
+impl MillimeterRange {
+    ///
+    /// Decode a range where an encoding of 0 denotes at most `base`
+    /// millimeters and each increment adds a millimeter, with the maximum
+    /// encoding being unbounded above.
+    ///
+    fn decode(value: u8, base: u8, max: u8) -> Self {
+        match value {
+            0 => MillimeterRange {
+                above: 0,
+                at_most: Some(base),
+            },
+            v if v == max => MillimeterRange {
+                above: base + v - 1,
+                at_most: None,
+            },
+            v => MillimeterRange {
+                above: base + v - 1,
+                at_most: Some(base + v),
+            },
+        }
+    }
+}
+
+///
+/// Module mechanical parameters, as described in bytes 128 and 129 of the
+/// DDR4 SPD.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mechanical {
+    pub height: MillimeterRange,
+    pub front_thickness: MillimeterRange,
+    pub back_thickness: MillimeterRange,
+}
+
+impl Mechanical {
+    pub fn from_spd(buf: &[u8]) -> Self {
+        Self::decode(
+            Offset::ModuleNominalHeight.within(buf),
+            Offset::ModuleMaximumThickness.within(buf),
+        )
+    }
+
+    pub fn decode(height: u8, thickness: u8) -> Self {
+        Self {
+            height: MillimeterRange::decode(height & 0b1_1111, 15, 0b1_1111),
+            front_thickness: MillimeterRange::decode(thickness & 0xf, 1, 0xf),
+            back_thickness: MillimeterRange::decode(thickness >> 4, 1, 0xf),
+        }
+    }
+
+    ///
+    /// Returns true if the module is guaranteed to be no taller than the
+    /// given height in millimeters.
+    ///
+    pub fn fits_height(&self, mm: u8) -> bool {
+        self.height.at_most.is_some_and(|h| h <= mm)
+    }
+
+    ///
+    /// Returns true if the module is guaranteed to be no thicker than the
+    /// given front and back thicknesses in millimeters.
+    ///
+    pub fn fits_thickness(&self, front: u8, back: u8) -> bool {
+        self.front_thickness.at_most.is_some_and(|t| t <= front)
+            && self.back_thickness.at_most.is_some_and(|t| t <= back)
+    }
+}
+
+///
+/// The hybrid media type encoded in bits 6:4 of the module type byte (byte 3)
+/// when the hybrid bit (bit 7) is set.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HybridMedia {
+    /// NVDIMM-N: DRAM backed by NAND on power loss.
+    NvdimmN,
+    /// NVDIMM-P: persistent media behind a transactional interface (DDR5).
+    NvdimmP,
+    Reserved(u8),
+}
+
+impl HybridMedia {
+    ///
+    /// Decode the module type byte for the given device type.  Returns
+    /// `None` if the module is not hybrid.
+    ///
+    pub fn decode(device: DeviceType, module_type: u8) -> Option<Self> {
+        if module_type & 0b1000_0000 == 0 {
+            return None;
+        }
+
+        let media = (module_type >> 4) & 0b111;
+
+        Some(match (device, media) {
+            (_, 0b001) => HybridMedia::NvdimmN,
+            (DeviceType::Ddr5, 0b010) => HybridMedia::NvdimmP,
+            (_, media) => HybridMedia::Reserved(media),
+        })
+    }
+
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        Self::decode(DeviceType::from_spd(buf)?, Offset::ModuleType.within(buf))
+    }
+
+    ///
+    /// Returns true if the media retains its contents across power loss.
+    ///
+    pub fn persistent(&self) -> bool {
+        matches!(self, HybridMedia::NvdimmN | HybridMedia::NvdimmP)
+    }
+}
+
+///
+/// The function class of a hybrid module function, per JESD245.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FunctionClass {
+    /// Byte-addressable, energy backed (NVDIMM-N).
+    ByteAddressableEnergyBacked,
+    /// Block addressed.
+    BlockAddressed,
+    /// Byte-addressable, without energy backing.
+    ByteAddressable,
+    Reserved(u8),
+}
+
+impl FunctionClass {
+    pub fn decode(class: u8) -> Self {
+        match class {
+            0x01 => FunctionClass::ByteAddressableEnergyBacked,
+            0x02 => FunctionClass::BlockAddressed,
+            0x03 => FunctionClass::ByteAddressable,
+            class => FunctionClass::Reserved(class),
+        }
+    }
+
+    ///
+    /// Returns true if data written through this class of function
+    /// survives power loss.
+    ///
+    pub fn persistent(&self) -> bool {
+        matches!(
+            self,
+            FunctionClass::ByteAddressableEnergyBacked
+                | FunctionClass::BlockAddressed
+        )
+    }
+}
+
+///
+/// A function interface descriptor from the hybrid module section:  bit 15
+/// indicates that the function is implemented, bits 9:5 hold its function
+/// class and bits 4:0 its function interface.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FunctionInterface {
+    pub class: FunctionClass,
+    pub interface: u8,
+}
+
+impl FunctionInterface {
+    ///
+    /// The maximum number of function interface descriptors.
+    ///
+    pub const MAX: usize = 8;
+
+    ///
+    /// Decode a descriptor, returning `None` if the function is not
+    /// implemented.
+    ///
+    pub fn decode(descriptor: u16) -> Option<Self> {
+        if descriptor & 0x8000 == 0 {
+            return None;
+        }
+
+        Some(Self {
+            class: FunctionClass::decode(((descriptor >> 5) & 0b1_1111) as u8),
+            interface: (descriptor & 0b1_1111) as u8,
+        })
+    }
+
+    ///
+    /// Iterate over the implemented functions of a hybrid DDR4 module.
+    ///
+    pub fn from_spd(buf: &[u8]) -> impl Iterator<Item = Self> + '_ {
+        let base = Offset::FunctionInterfaceBase.to_usize();
+        let limit = Offset::FunctionInterfaceLimit.to_usize();
+
+        buf[base..=limit]
+            .chunks(2)
+            .filter_map(|d| Self::decode(u16::from_le_bytes([d[0], d[1]])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::string::ToString;
+
+    #[test]
+    fn raw_card() {
+        let card = RawCard::decode(0x01, 0);
+        assert_eq!(card.design, RawCardDesign::Reference(1));
+        assert_eq!(card.revision, 0);
+        assert_eq!(card.to_string(), "B rev 0");
+
+        let card = RawCard::decode(0b0100_0000 | 25, 0);
+        assert_eq!(card.to_string(), "Z rev 2");
+
+        let card = RawCard::decode(26, 0);
+        assert_eq!(card.to_string(), "AA rev 0");
+
+        let card = RawCard::decode(0b1000_0000, 0);
+        assert_eq!(card.to_string(), "AF rev 0");
+
+        let card = RawCard::decode(0b1001_1110, 0);
+        assert_eq!(card.to_string(), "BJ rev 0");
+
+        let card = RawCard::decode(0b0001_1111, 0);
+        assert_eq!(card.design, RawCardDesign::NotReference);
+
+        assert_eq!(RawCard::decode(0b0110_0000, 0).revision, 3);
+        assert_eq!(RawCard::decode(0b0110_0000, 0b0010_0000).revision, 4);
+        assert_eq!(RawCard::decode(0b0110_0000, 0b1111_1111).revision, 10);
+        assert_eq!(RawCard::decode(0b0100_0000, 0b1110_0000).revision, 2);
+    }
+
+    #[test]
+    fn mechanical() {
+        let m = Mechanical::decode(0b0011_0000 | 16, 0x21);
+
+        assert_eq!(
+            m.height,
+            MillimeterRange {
+                above: 30,
+                at_most: Some(31)
+            }
+        );
+        assert_eq!(
+            m.front_thickness,
+            MillimeterRange {
+                above: 1,
+                at_most: Some(2)
+            }
+        );
+        assert_eq!(m.back_thickness.at_most, Some(3));
+        assert!(m.fits_height(31));
+        assert!(!m.fits_height(30));
+        assert!(m.fits_thickness(2, 3));
+        assert!(!m.fits_thickness(2, 2));
+
+        let m = Mechanical::decode(0, 0xf0);
+        assert_eq!(m.height.at_most, Some(15));
+        assert_eq!(m.front_thickness.at_most, Some(1));
+        assert_eq!(m.back_thickness.at_most, None);
+
+        let m = Mechanical::decode(0b1_1111, 0);
+        assert_eq!(m.height.above, 45);
+        assert!(!m.fits_height(u8::MAX));
+    }
+
+    #[test]
+    fn hybrid() {
+        assert_eq!(HybridMedia::decode(DeviceType::Ddr4, 0x02), None);
+        assert_eq!(
+            HybridMedia::decode(DeviceType::Ddr4, 0x91),
+            Some(HybridMedia::NvdimmN)
+        );
+        assert_eq!(
+            HybridMedia::decode(DeviceType::Ddr4, 0xa1),
+            Some(HybridMedia::Reserved(2))
+        );
+        assert_eq!(
+            HybridMedia::decode(DeviceType::Ddr5, 0xa1),
+            Some(HybridMedia::NvdimmP)
+        );
+        assert!(HybridMedia::NvdimmP.persistent());
+
+        let mut buf = [0u8; crate::MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x003] = 0x91;
+        buf[0x0cc] = 0x21;
+        buf[0x0cd] = 0x80;
+        buf[0x0d0] = 0x41;
+        buf[0x0d1] = 0x80;
+        assert_eq!(HybridMedia::from_spd(&buf), Some(HybridMedia::NvdimmN));
+
+        let mut functions = FunctionInterface::from_spd(&buf);
+        let f = functions.next().unwrap();
+        assert_eq!(f.class, FunctionClass::ByteAddressableEnergyBacked);
+        assert_eq!(f.interface, 1);
+        assert!(f.class.persistent());
+        assert_eq!(
+            functions.next().unwrap().class,
+            FunctionClass::BlockAddressed
+        );
+        assert_eq!(functions.next(), None);
+    }
+}
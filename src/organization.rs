@@ -0,0 +1,147 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::Offset;
+
+///
+/// The organization of a DDR4 module, from bytes 4, 6, 12 and 13.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Organization {
+    /// Density per die, in megabits.
+    pub density: u32,
+    /// Dies per package.
+    pub dies: u8,
+    /// The package is a 3DS (single load) stack, in which each die forms its
+    /// own logical rank.
+    pub stacked: bool,
+    /// SDRAM I/O width, in bits.
+    pub width: u8,
+    /// Package ranks.
+    pub ranks: u8,
+    /// Primary bus width, in bits.
+    pub bus_width: u8,
+    /// Bus width extension (ECC), in bits.
+    pub ecc_width: u8,
+}
+
+impl Organization {
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        let package = Offset::PrimarySDRAMPackageType.within(buf);
+        let organization = Offset::ModuleOrganization.within(buf);
+        let bus = Offset::ModuleMemoryBusWidth.within(buf);
+
+        let density = match Offset::SDRAMDensity.within(buf) & 0b1111 {
+            d @ 0..=7 => 256 << d,
+            8 => 12 << 10,
+            9 => 24 << 10,
+            _ => return None,
+        };
+
+        let width = match organization & 0b111 {
+            w @ 0..=3 => 4 << w,
+            _ => return None,
+        };
+
+        let bus_width = match bus & 0b111 {
+            w @ 0..=3 => 8 << w,
+            _ => return None,
+        };
+
+        let ecc_width = match (bus >> 3) & 0b11 {
+            0b00 => 0,
+            0b01 => 8,
+            _ => return None,
+        };
+
+        let monolithic = package & 0b1000_0000 == 0;
+
+        Some(Self {
+            density,
+            dies: if monolithic {
+                1
+            } else {
+                ((package >> 4) & 0b111) + 1
+            },
+            stacked: !monolithic && package & 0b11 == 0b10,
+            width,
+            ranks: ((organization >> 3) & 0b111) + 1,
+            bus_width,
+            ecc_width,
+        })
+    }
+
+    ///
+    /// Logical ranks:  for a 3DS module, each die of each package rank.
+    ///
+    pub fn logical_ranks(&self) -> u8 {
+        if self.stacked {
+            self.ranks * self.dies
+        } else {
+            self.ranks
+        }
+    }
+
+    ///
+    /// Capacity of the module in bytes, excluding ECC:  the density of each
+    /// die, times the dies across the primary bus, times the logical ranks.
+    ///
+    pub fn capacity(&self) -> u64 {
+        let packages = (self.bus_width / self.width) as u64;
+        let bits = (self.density as u64) << 20;
+
+        packages * bits / 8 * self.logical_ranks() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    const GIB: u64 = 1 << 30;
+
+    fn image(density: u8, package: u8, org: u8, bus: u8) -> [u8; MAX_SIZE] {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[Offset::SDRAMDensity.to_usize()] = density;
+        buf[Offset::PrimarySDRAMPackageType.to_usize()] = package;
+        buf[Offset::ModuleOrganization.to_usize()] = org;
+        buf[Offset::ModuleMemoryBusWidth.to_usize()] = bus;
+        buf
+    }
+
+    #[test]
+    fn capacity() {
+        //
+        // 32 GiB 2Rx4 RDIMM of 8 Gb monolithic dies, with ECC.
+        //
+        let org = Organization::from_spd(&image(0x85, 0, 0x08, 0x0b)).unwrap();
+        assert_eq!(org.ranks, 2);
+        assert_eq!((org.bus_width, org.ecc_width), (64, 8));
+        assert_eq!(org.capacity(), 32 * GIB);
+
+        //
+        // 256 GiB 3DS RDIMM of 16 Gb x4 dies:  2 package ranks of 4-high
+        // stacks.
+        //
+        let org =
+            Organization::from_spd(&image(0x86, 0xb2, 0x08, 0x0b)).unwrap();
+        assert_eq!((org.dies, org.logical_ranks()), (4, 8));
+        assert_eq!(org.capacity(), 256 * GIB);
+
+        //
+        // In a dual-die package that is not a 3DS stack, each die is
+        // already counted as a package rank.
+        //
+        let org =
+            Organization::from_spd(&image(0x05, 0x91, 0x08, 0x03)).unwrap();
+        assert_eq!(org.logical_ranks(), 2);
+        assert_eq!(org.capacity(), 32 * GIB);
+
+        assert_eq!(Organization::from_spd(&image(0x0a, 0, 0, 0)), None);
+        assert_eq!(Organization::from_spd(&image(0, 0, 0x04, 0)), None);
+    }
+}
@@ -0,0 +1,198 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{
+    Function, Page, SelectAddress, DDR5_MAX_SIZE, DDR5_PAGE_SIZE, MAX_SIZE,
+    PAGE_SIZE,
+};
+
+///
+/// A write transaction that selects a page:  the device code to address and
+/// the (at most two) bytes to send.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SelectWrite {
+    pub code: u8,
+    buf: [u8; 2],
+    len: usize,
+}
+
+impl SelectWrite {
+    pub fn bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+///
+/// A scheme by which an SPD image larger than the 8-bit address space is
+/// divided into pages.  Bus drivers written against this trait work for
+/// both EE1004 (DDR4) and SPD5118 (DDR5) devices.
+///
+pub trait PageSelector {
+    ///
+    /// The size of the image, in bytes.
+    ///
+    fn size(&self) -> usize;
+
+    ///
+    /// The size of a page, in bytes.
+    ///
+    fn page_size(&self) -> usize;
+
+    ///
+    /// The address to send to the device to access the given offset once
+    /// its page has been selected.
+    ///
+    fn address(&self, offset: usize) -> u8;
+
+    ///
+    /// The write that selects the given page, or `None` if there is no such
+    /// page.
+    ///
+    fn select(&self, page: Page) -> Option<SelectWrite>;
+
+    ///
+    /// The page that must be selected to access the given offset, along with
+    /// the address to send within that page.  Returns `None` if the offset
+    /// is beyond the end of the image.
+    ///
+    fn locate(&self, offset: usize) -> Option<(Page, u8)> {
+        if offset >= self.size() {
+            return None;
+        }
+
+        Some((
+            Page((offset / self.page_size()) as u8),
+            self.address(offset),
+        ))
+    }
+}
+
+///
+/// EE1004 paging:  two 256-byte pages, selected by a write to the SPA0 or
+/// SPA1 device code (with two don't-care bytes) that applies to every device
+/// on the bus.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Ee1004Pages;
+
+impl PageSelector for Ee1004Pages {
+    fn size(&self) -> usize {
+        MAX_SIZE
+    }
+
+    fn page_size(&self) -> usize {
+        PAGE_SIZE
+    }
+
+    fn address(&self, offset: usize) -> u8 {
+        offset as u8
+    }
+
+    fn select(&self, page: Page) -> Option<SelectWrite> {
+        Some(SelectWrite {
+            code: Function::PageAddress(page).to_device_code()?,
+            buf: [0, 0],
+            len: 2,
+        })
+    }
+}
+
+///
+/// SPD5118 paging:  eight 128-byte pages, selected by writing MR11 of the
+/// hub at the given select address.  NVM addresses have bit 7 set.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Spd5118Pages(pub SelectAddress);
+
+impl Spd5118Pages {
+    const MR11: u8 = 11;
+}
+
+impl PageSelector for Spd5118Pages {
+    fn size(&self) -> usize {
+        DDR5_MAX_SIZE
+    }
+
+    fn page_size(&self) -> usize {
+        DDR5_PAGE_SIZE
+    }
+
+    fn address(&self, offset: usize) -> u8 {
+        0x80 | (offset % DDR5_PAGE_SIZE) as u8
+    }
+
+    fn select(&self, page: Page) -> Option<SelectWrite> {
+        if page.0 as usize >= DDR5_MAX_SIZE / DDR5_PAGE_SIZE {
+            return None;
+        }
+
+        Some(SelectWrite {
+            code: Function::Memory(self.0).to_device_code()?,
+            buf: [Self::MR11, page.0],
+            len: 2,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpdEmulator;
+
+    ///
+    /// Read a byte through the emulator using nothing but the trait.
+    ///
+    fn read<P: PageSelector>(
+        pages: &P,
+        emul: &mut SpdEmulator,
+        mem: u8,
+        offset: usize,
+    ) -> u8 {
+        let (page, address) = pages.locate(offset).unwrap();
+        let select = pages.select(page).unwrap();
+        assert!(emul.on_write(select.code, select.bytes()));
+
+        let mut buf = [0u8];
+        assert!(emul.on_write(mem, &[address]));
+        assert!(emul.on_read(mem, &mut buf));
+        buf[0]
+    }
+
+    #[test]
+    fn ee1004() {
+        let pages = Ee1004Pages;
+        assert_eq!(pages.locate(0x17f), Some((Page(1), 0x7f)));
+        assert_eq!(pages.locate(0x200), None);
+        assert_eq!(pages.select(Page(1)).unwrap().code, 0x37);
+        assert_eq!(pages.select(Page(2)), None);
+
+        let mut image = [0u8; MAX_SIZE];
+        image[0x142] = 0xab;
+
+        let sa = SelectAddress::new(0);
+        let mut emul = SpdEmulator::ee1004(sa, &image);
+        let mem = Function::Memory(sa).to_device_code().unwrap();
+        assert_eq!(read(&pages, &mut emul, mem, 0x142), 0xab);
+    }
+
+    #[test]
+    fn spd5118() {
+        let sa = SelectAddress::new(1);
+        let pages = Spd5118Pages(sa);
+        assert_eq!(pages.locate(0x209), Some((Page(4), 0x89)));
+        assert_eq!(pages.locate(0x400), None);
+        assert_eq!(pages.select(Page(4)).unwrap().bytes(), &[11, 4]);
+        assert_eq!(pages.select(Page(8)), None);
+
+        let mut image = [0u8; DDR5_MAX_SIZE];
+        image[0x209] = b'M';
+
+        let mut emul = SpdEmulator::spd5118(sa, &image);
+        let mem = Function::Memory(sa).to_device_code().unwrap();
+        assert_eq!(read(&pages, &mut emul, mem, 0x209), b'M');
+    }
+}
@@ -0,0 +1,226 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::summary::Identity;
+use crate::{
+    ddr5, DeviceType, Error, Offset, Picoseconds, Summary, Timing,
+    DDR5_MAX_SIZE, MAX_SIZE,
+};
+use core::ops::RangeInclusive;
+
+///
+/// The base configuration section, which holds the timing parameters in
+/// both generations.
+///
+const BASE_CONFIGURATION: RangeInclusive<usize> = 0..=0x7f;
+
+///
+/// Which decoded facts can be extracted from the bytes received so far.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Available {
+    /// The device type (byte 2) has been received.
+    pub device_type: bool,
+    /// The base configuration section (bytes 0 to 0x7f) has been received.
+    pub timings: bool,
+    /// The manufacturing information has been received.
+    pub identity: bool,
+    /// Every byte of the image has been received.
+    pub complete: bool,
+}
+
+///
+/// A push-style parser that accumulates an image as it arrives from a slow
+/// bus, in any order, and decodes facts as soon as the bytes they depend on
+/// have been received.
+///
+#[derive(Clone, Debug)]
+pub struct StreamingParser {
+    buf: [u8; DDR5_MAX_SIZE],
+    received: [u64; DDR5_MAX_SIZE / 64],
+}
+
+impl Default for StreamingParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingParser {
+    pub fn new() -> Self {
+        Self {
+            buf: [0; DDR5_MAX_SIZE],
+            received: [0; DDR5_MAX_SIZE / 64],
+        }
+    }
+
+    ///
+    /// Accept a chunk of the image starting at the given offset.  Returns an
+    /// error (accepting nothing) if the chunk extends beyond the largest
+    /// supported image.
+    ///
+    pub fn feed(&mut self, offset: usize, chunk: &[u8]) -> Result<(), Error> {
+        let end = match offset.checked_add(chunk.len()) {
+            Some(end) if end <= DDR5_MAX_SIZE => end,
+            _ => return Err(Error::BufferTooSmall),
+        };
+
+        self.buf[offset..end].copy_from_slice(chunk);
+
+        for o in offset..end {
+            self.received[o / 64] |= 1 << (o % 64);
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Returns true if every byte in the range has been received.
+    ///
+    pub fn received(&self, range: RangeInclusive<usize>) -> bool {
+        range.into_iter().all(|o| {
+            o < DDR5_MAX_SIZE && self.received[o / 64] & (1 << (o % 64)) != 0
+        })
+    }
+
+    pub fn device_type(&self) -> Option<DeviceType> {
+        let offset = Offset::DRAMDeviceType.to_usize();
+
+        if self.received(offset..=offset) {
+            DeviceType::from_spd(&self.buf)
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// The size of the image, once the device type is known.
+    ///
+    pub fn size(&self) -> Option<usize> {
+        Some(match self.device_type()? {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => MAX_SIZE,
+            DeviceType::Ddr5 => DDR5_MAX_SIZE,
+        })
+    }
+
+    pub fn available(&self) -> Available {
+        let device_type = self.device_type();
+
+        Available {
+            device_type: device_type.is_some(),
+            timings: device_type.is_some() && self.received(BASE_CONFIGURATION),
+            identity: device_type.is_some_and(|d| {
+                let identity = Identity::of(d);
+                self.received(identity.manufacturer..=identity.dram_stepping)
+            }),
+            complete: self.size().is_some_and(|s| self.received(0..=s - 1)),
+        }
+    }
+
+    pub fn tck_min(&self) -> Option<Picoseconds> {
+        self.timing(Timing::TCkAvgMin, ddr5::Timing::TCkAvgMin)
+    }
+
+    pub fn taa_min(&self) -> Option<Picoseconds> {
+        self.timing(Timing::TAAMin, ddr5::Timing::TAAMin)
+    }
+
+    fn timing(&self, ddr4: Timing, ddr5: ddr5::Timing) -> Option<Picoseconds> {
+        if !self.available().timings {
+            return None;
+        }
+
+        match self.device_type()? {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => {
+                Some(ddr4.within(&self.buf))
+            }
+            DeviceType::Ddr5 => Some(ddr5.within(&self.buf)),
+        }
+    }
+
+    ///
+    /// Decode a summary once every byte up to the end of the identity fields
+    /// has been received.
+    ///
+    pub fn summary(&self) -> Option<Summary> {
+        let identity = Identity::of(self.device_type()?);
+
+        if !self.received(0..=identity.dram_stepping) {
+            return None;
+        }
+
+        Summary::from_spd(&self.buf[..self.size()?])
+    }
+
+    ///
+    /// The complete image, once it has been received.
+    ///
+    pub fn image(&self) -> Option<&[u8]> {
+        if self.available().complete {
+            Some(&self.buf[..self.size()?])
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::update_crc;
+
+    #[test]
+    fn ddr4() {
+        let mut image = [0u8; MAX_SIZE];
+        image[0x002] = 0x0c;
+        image[0x012] = 0x05;
+        image[0x018] = 0x6e;
+        image[0x149..0x14c].copy_from_slice(b"ABC");
+        update_crc(&mut image);
+
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.available(), Available::default());
+
+        parser.feed(0, &image[..4]).unwrap();
+        assert_eq!(parser.device_type(), Some(DeviceType::Ddr4));
+        assert_eq!(parser.size(), Some(MAX_SIZE));
+        assert!(!parser.available().timings);
+        assert_eq!(parser.tck_min(), None);
+
+        parser.feed(4, &image[4..0x80]).unwrap();
+        assert!(parser.available().timings);
+        assert_eq!(parser.tck_min(), Some(Picoseconds(625)));
+        assert_eq!(parser.taa_min(), Some(Picoseconds(13750)));
+
+        //
+        // Page 1 arriving before the rest of page 0 provides the identity,
+        // but not the summary.
+        //
+        parser.feed(0x100, &image[0x100..]).unwrap();
+        assert!(parser.available().identity);
+        assert_eq!(parser.summary(), None);
+        assert_eq!(parser.image(), None);
+
+        parser.feed(0x80, &image[0x80..0x100]).unwrap();
+        assert!(parser.available().complete);
+        assert_eq!(parser.summary().unwrap().crc_valid, Some(true));
+        assert_eq!(parser.image(), Some(&image[..]));
+    }
+
+    #[test]
+    fn ddr5() {
+        let mut parser = StreamingParser::new();
+        parser.feed(0, &[0x30, 0x10, 0x12]).unwrap();
+        assert_eq!(parser.size(), Some(DDR5_MAX_SIZE));
+
+        parser.feed(0x200, &[0; 0x2b]).unwrap();
+        assert!(parser.available().identity);
+        assert!(!parser.available().complete);
+
+        assert_eq!(parser.feed(0x3f0, &[0; 0x20]), Err(Error::BufferTooSmall));
+        assert_eq!(parser.feed(usize::MAX, &[0]), Err(Error::BufferTooSmall));
+    }
+}
@@ -0,0 +1,318 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{Page, PageSelector, SelectWrite, WRITE_PAGE_SIZE};
+use core::cmp;
+
+///
+/// Where a field lives:  the page that must be selected and the address to
+/// send within that page.  Returns `None` if the offset is beyond the end of
+/// the image.
+///
+pub fn get_field<P: PageSelector>(
+    pages: &P,
+    offset: impl Into<usize>,
+) -> Option<(Page, u8)> {
+    pages.locate(offset.into())
+}
+
+///
+/// The page that must be selected to set a field, along with the bytes to
+/// write to the memory device code once it has been.
+///
+pub fn set_field<P: PageSelector>(
+    pages: &P,
+    offset: impl Into<usize>,
+    value: u8,
+) -> Option<(Page, [u8; 2])> {
+    let (page, address) = pages.locate(offset.into())?;
+    Some((page, [address, value]))
+}
+
+///
+/// A single bus operation in a plan.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Select a page.
+    Select(SelectWrite),
+
+    /// Write `address` to the memory device code, then read `len` bytes.
+    Read { code: u8, address: u8, len: usize },
+
+    /// Write `address` followed by the data to the memory device code.  The
+    /// data never crosses a write page boundary.
+    Write {
+        code: u8,
+        address: u8,
+        data: [u8; WRITE_PAGE_SIZE],
+        len: usize,
+    },
+}
+
+impl Operation {
+    ///
+    /// For a write, the data to be written.
+    ///
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Operation::Write { data, len, .. } => &data[..*len],
+            _ => &[],
+        }
+    }
+}
+
+///
+/// Plans the bus operations needed to access ranges of an image, tracking
+/// the currently selected page so that a page is only selected when it
+/// changes.  The planner assumes it is the only agent changing the page;
+/// call [`Planner::invalidate`] if that is not the case.
+///
+#[derive(Clone, Debug)]
+pub struct Planner<P> {
+    pages: P,
+    code: u8,
+    current: Option<Page>,
+}
+
+impl<P: PageSelector> Planner<P> {
+    ///
+    /// Construct a planner for the device at the given memory device code.
+    /// The selected page is initially unknown.
+    ///
+    pub fn new(pages: P, code: u8) -> Self {
+        Self {
+            pages,
+            code,
+            current: None,
+        }
+    }
+
+    ///
+    /// Forget the selected page, forcing the next plan to select one.
+    ///
+    pub fn invalidate(&mut self) {
+        self.current = None;
+    }
+
+    pub fn read(&mut self, offset: usize, len: usize) -> Plan<'_, P> {
+        Plan {
+            planner: self,
+            offset,
+            end: offset.saturating_add(len),
+            data: None,
+        }
+    }
+
+    pub fn write<'a>(
+        &'a mut self,
+        offset: usize,
+        data: &'a [u8],
+    ) -> Plan<'a, P> {
+        Plan {
+            planner: self,
+            offset,
+            end: offset.saturating_add(data.len()),
+            data: Some(data),
+        }
+    }
+
+    pub fn get_field(&mut self, offset: impl Into<usize>) -> Plan<'_, P> {
+        self.read(offset.into(), 1)
+    }
+
+    pub fn set_field<'a>(
+        &'a mut self,
+        offset: impl Into<usize>,
+        value: &'a u8,
+    ) -> Plan<'a, P> {
+        self.write(offset.into(), core::slice::from_ref(value))
+    }
+}
+
+///
+/// An iterator over the operations needed to read or write a range.  If any
+/// part of the range lies beyond the end of the image, iteration stops
+/// there.
+///
+pub struct Plan<'a, P> {
+    planner: &'a mut Planner<P>,
+    offset: usize,
+    end: usize,
+    data: Option<&'a [u8]>,
+}
+
+impl<P: PageSelector> Planner<P> {
+    ///
+    /// The next operation needed to read (or, given data, write)
+    /// `*offset..end`, advancing `offset` past any bytes it transfers.  For
+    /// a write, `data` holds the bytes up to `end`.
+    ///
+    pub(crate) fn step(
+        &mut self,
+        offset: &mut usize,
+        end: usize,
+        data: Option<&[u8]>,
+    ) -> Option<Operation> {
+        if *offset >= end {
+            return None;
+        }
+
+        let (page, address) = self.pages.locate(*offset)?;
+
+        if self.current != Some(page) {
+            self.current = Some(page);
+            return self.pages.select(page).map(Operation::Select);
+        }
+
+        let page_size = self.pages.page_size();
+        let len = cmp::min(end - *offset, page_size - *offset % page_size);
+
+        let op = match data {
+            None => Operation::Read {
+                code: self.code,
+                address,
+                len,
+            },
+            Some(data) => {
+                let len =
+                    cmp::min(len, WRITE_PAGE_SIZE - *offset % WRITE_PAGE_SIZE);
+
+                let start = data.len() - (end - *offset);
+                let mut buf = [0u8; WRITE_PAGE_SIZE];
+                buf[..len].copy_from_slice(&data[start..start + len]);
+
+                Operation::Write {
+                    code: self.code,
+                    address,
+                    data: buf,
+                    len,
+                }
+            }
+        };
+
+        *offset += match op {
+            Operation::Read { len, .. } | Operation::Write { len, .. } => len,
+            Operation::Select(_) => 0,
+        };
+
+        Some(op)
+    }
+}
+
+impl<P: PageSelector> Iterator for Plan<'_, P> {
+    type Item = Operation;
+
+    fn next(&mut self) -> Option<Operation> {
+        self.planner.step(&mut self.offset, self.end, self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ddr5, Ee1004Pages, Function, Offset, SelectAddress, Spd5118Pages,
+        SpdEmulator, MAX_SIZE,
+    };
+
+    fn run<P: PageSelector>(plan: Plan<'_, P>, emul: &mut SpdEmulator) {
+        let mut buf = [0u8; MAX_SIZE];
+
+        for op in plan {
+            match op {
+                Operation::Select(select) => {
+                    assert!(emul.on_write(select.code, select.bytes()));
+                }
+                Operation::Read { code, address, len } => {
+                    assert!(emul.on_write(code, &[address]));
+                    assert!(emul.on_read(code, &mut buf[..len]));
+                }
+                Operation::Write { code, address, .. } => {
+                    let mut bytes = [0u8; WRITE_PAGE_SIZE + 1];
+                    bytes[0] = address;
+                    bytes[1..=op.data().len()].copy_from_slice(op.data());
+                    assert!(emul.on_write(code, &bytes[..=op.data().len()]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(
+            get_field(&Ee1004Pages, Offset::ModuleManufacturingLocation),
+            Some((Page(1), 0x42))
+        );
+
+        let pages = Spd5118Pages(SelectAddress::new(0));
+        assert_eq!(
+            set_field(&pages, ddr5::Offset::ModuleManufacturingLocation, 7),
+            Some((Page(4), [0x82, 7]))
+        );
+        assert_eq!(get_field(&pages, 0x400usize), None);
+    }
+
+    #[test]
+    fn read() {
+        let sa = SelectAddress::new(0);
+        let code = Function::Memory(sa).to_device_code().unwrap();
+        let mut planner = Planner::new(Ee1004Pages, code);
+
+        let mut plan = planner.read(0xf0, 0x20);
+        let select =
+            |page| Operation::Select(Ee1004Pages.select(page).unwrap());
+        assert_eq!(plan.next(), Some(select(Page(0))));
+        assert_eq!(
+            plan.next(),
+            Some(Operation::Read {
+                code,
+                address: 0xf0,
+                len: 0x10
+            })
+        );
+        assert_eq!(plan.next(), Some(select(Page(1))));
+        assert_eq!(
+            plan.next(),
+            Some(Operation::Read {
+                code,
+                address: 0,
+                len: 0x10
+            })
+        );
+        assert_eq!(plan.next(), None);
+
+        // The page is still selected, so no select is needed.
+        assert_eq!(planner.get_field(Offset::DRAMStepping).count(), 1);
+        planner.invalidate();
+        assert_eq!(planner.get_field(Offset::DRAMStepping).count(), 2);
+
+        // Ranges beyond the device plan nothing, even if they overflow.
+        assert_eq!(planner.read(usize::MAX, 2).count(), 0);
+        assert_eq!(planner.write(usize::MAX, &[1, 2]).count(), 0);
+    }
+
+    #[test]
+    fn write() {
+        let sa = SelectAddress::new(0);
+        let code = Function::Memory(sa).to_device_code().unwrap();
+        let mut emul = SpdEmulator::spd5118(sa, &[0; crate::DDR5_MAX_SIZE]);
+        let mut planner = Planner::new(Spd5118Pages(sa), code);
+
+        let data = [0xa5u8; 40];
+        assert_eq!(planner.write(0x178, &data).count(), 5);
+        planner.invalidate();
+        run(planner.write(0x178, &data), &mut emul);
+        assert!(emul.image()[0x178..0x1a0].iter().all(|&b| b == 0xa5));
+        assert_eq!(emul.image()[0x1a0], 0);
+
+        run(
+            planner.set_field(ddr5::Offset::DRAMStepping, &0x42),
+            &mut emul,
+        );
+        assert_eq!(emul.image()[0x22a], 0x42);
+    }
+}
@@ -0,0 +1,334 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::ddr5::{BlockProtection, OverrideSequence, PROTECTION_BLOCK_SIZE};
+use crate::{
+    regions, DeviceType, Function, Region, BLOCK_SIZE, DDR5_MAX_SIZE, MAX_SIZE,
+};
+
+///
+/// Why an audit of a [`ProtectionState`] failed.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AuditError {
+    /// The protection of the block has not been read.
+    Unknown(u8),
+    /// The block is not protected.
+    Unprotected(u8),
+}
+
+///
+/// The EE1004 commands that change protection, each a write (of two
+/// don't-care bytes) to the device code of the function.  Protection can
+/// only be cleared for all blocks at once, so clearing any block requires
+/// clearing all of them and then reprotecting the others.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ee1004Change {
+    /// Issue CWP first.
+    pub clear_all: bool,
+    /// The blocks to protect with SWPn, as a bitmap.
+    pub set: u8,
+}
+
+impl Ee1004Change {
+    pub fn functions(&self) -> impl Iterator<Item = Function> + '_ {
+        let clear = if self.clear_all {
+            Some(Function::ClearAllWriteProtection)
+        } else {
+            None
+        };
+
+        clear.into_iter().chain(
+            (0..4)
+                .filter(move |&b| self.set & (1 << b) != 0)
+                .map(Function::ProtectionStatus),
+        )
+    }
+}
+
+///
+/// The commands needed to reach a desired protection policy.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ProtectionChange {
+    /// The device already has the desired protection.
+    Unchanged,
+    Ee1004(Ee1004Change),
+    Spd5118(OverrideSequence),
+}
+
+///
+/// The per-block write protection of a device, as learned by reading it:
+/// the four 128-byte blocks of an EE1004 (from RPSn) or the sixteen 64-byte
+/// blocks of an SPD5118 (from MR12 and MR13).
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProtectionState {
+    device_type: DeviceType,
+    protected: u16,
+    known: u16,
+}
+
+impl ProtectionState {
+    ///
+    /// A state in which the protection of every block is unknown.
+    ///
+    pub fn new(device_type: DeviceType) -> Self {
+        Self {
+            device_type,
+            protected: 0,
+            known: 0,
+        }
+    }
+
+    pub fn device_type(&self) -> DeviceType {
+        self.device_type
+    }
+
+    pub fn nblocks(&self) -> u8 {
+        (self.size() / self.block_size()) as u8
+    }
+
+    pub fn block_size(&self) -> usize {
+        match self.device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => BLOCK_SIZE,
+            DeviceType::Ddr5 => PROTECTION_BLOCK_SIZE,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self.device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => MAX_SIZE,
+            DeviceType::Ddr5 => DDR5_MAX_SIZE,
+        }
+    }
+
+    fn all(&self) -> u16 {
+        u16::MAX >> (16 - self.nblocks())
+    }
+
+    ///
+    /// Record the result of an EE1004 RPSn read, which is ACK'd only if the
+    /// block is not protected.
+    ///
+    pub fn record_rps(&mut self, block: u8, ack: bool) {
+        if block < self.nblocks() {
+            self.known |= 1 << block;
+
+            if ack {
+                self.protected &= !(1 << block);
+            } else {
+                self.protected |= 1 << block;
+            }
+        }
+    }
+
+    ///
+    /// Record the protection of all blocks, as read from MR12 and MR13 of an
+    /// SPD5118.
+    ///
+    pub fn record(&mut self, protection: BlockProtection) {
+        self.known = self.all();
+        self.protected = protection.0 & self.known;
+    }
+
+    ///
+    /// Returns `None` if the block is out of range or has not been read.
+    ///
+    pub fn is_protected(&self, block: u8) -> Option<bool> {
+        if block < self.nblocks() && self.known & (1 << block) != 0 {
+            Some(self.protected & (1 << block) != 0)
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Returns `None` if the offset is out of range or the protection of its
+    /// block has not been read.
+    ///
+    pub fn is_writable(&self, offset: usize) -> Option<bool> {
+        if offset >= self.size() {
+            return None;
+        }
+
+        self.is_protected((offset / self.block_size()) as u8)
+            .map(|p| !p)
+    }
+
+    ///
+    /// The bitmap of blocks containing any byte of the given region, for
+    /// use with [`ProtectionState::change_to`].
+    ///
+    pub fn blocks_of(&self, region: Region) -> u16 {
+        regions(self.device_type)
+            .filter(|(_, r)| *r == region)
+            .fold(0, |mask, (range, _)| {
+                let (base, limit) = range.into_inner();
+                (base / self.block_size()..=limit / self.block_size())
+                    .fold(mask, |mask, block| mask | 1 << block)
+            })
+    }
+
+    ///
+    /// Confirm that every block is known to be protected, as required
+    /// before a module ships.
+    ///
+    pub fn audit(&self) -> Result<(), AuditError> {
+        for block in 0..self.nblocks() {
+            match self.is_protected(block) {
+                None => return Err(AuditError::Unknown(block)),
+                Some(false) => return Err(AuditError::Unprotected(block)),
+                Some(true) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// The commands that take the device from this state to the desired
+    /// protection, given as a bitmap of blocks.  Returns `None` if the
+    /// protection of any block is unknown.
+    ///
+    pub fn change_to(&self, desired: u16) -> Option<ProtectionChange> {
+        if self.known != self.all() {
+            return None;
+        }
+
+        let desired = desired & self.all();
+
+        if desired == self.protected {
+            return Some(ProtectionChange::Unchanged);
+        }
+
+        let clear = self.protected & !desired != 0;
+
+        Some(match self.device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => {
+                ProtectionChange::Ee1004(Ee1004Change {
+                    clear_all: clear,
+                    set: if clear {
+                        desired as u8
+                    } else {
+                        (desired & !self.protected) as u8
+                    },
+                })
+            }
+            DeviceType::Ddr5 => ProtectionChange::Spd5118(
+                OverrideSequence::new(BlockProtection(desired), clear),
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MockSpdDevice, SelectAddress, SpdBus};
+
+    #[test]
+    fn ddr4() {
+        let sa = SelectAddress::new(0);
+        let mut dev = MockSpdDevice::ddr4(sa, &[0; MAX_SIZE]);
+        let mut state = ProtectionState::new(DeviceType::Ddr4);
+        assert_eq!(state.audit(), Err(AuditError::Unknown(0)));
+        assert_eq!(state.change_to(0xf), None);
+
+        let read = |dev: &mut MockSpdDevice, state: &mut ProtectionState| {
+            for block in 0..state.nblocks() {
+                let code =
+                    Function::ProtectionStatus(block).to_device_code().unwrap();
+                state.record_rps(block, dev.read(code, &mut [0]).is_ok());
+            }
+        };
+
+        let apply = |dev: &mut MockSpdDevice, change| match change {
+            ProtectionChange::Ee1004(change) => {
+                for f in change.functions() {
+                    dev.write(f.to_device_code().unwrap(), &[0, 0]).unwrap();
+                }
+            }
+            _ => panic!("unexpected change {:?}", change),
+        };
+
+        read(&mut dev, &mut state);
+        assert_eq!(state.is_writable(0x142), Some(true));
+        assert_eq!(state.audit(), Err(AuditError::Unprotected(0)));
+
+        let change = state.change_to(0b0110).unwrap();
+        assert_eq!(
+            change,
+            ProtectionChange::Ee1004(Ee1004Change {
+                clear_all: false,
+                set: 0b0110
+            })
+        );
+        apply(&mut dev, change);
+        read(&mut dev, &mut state);
+        assert_eq!(state.is_writable(0x142), Some(false));
+        assert_eq!(state.is_writable(0x200), None);
+
+        //
+        // Unprotecting block 1 clears everything, then reprotects block 3.
+        //
+        let change = state.change_to(0b1100).unwrap();
+        apply(&mut dev, change);
+        read(&mut dev, &mut state);
+        assert_eq!(state.is_protected(1), Some(false));
+        assert_eq!(state.is_protected(2), Some(true));
+        assert_eq!(state.is_protected(3), Some(true));
+
+        apply(&mut dev, state.change_to(0xf).unwrap());
+        read(&mut dev, &mut state);
+        assert_eq!(state.audit(), Ok(()));
+        assert_eq!(state.change_to(0xff), Some(ProtectionChange::Unchanged));
+    }
+
+    #[test]
+    fn ddr5() {
+        let mut state = ProtectionState::new(DeviceType::Ddr5);
+        assert_eq!(state.nblocks(), 16);
+        assert_eq!(state.is_writable(0x3ff), None);
+
+        state.record(BlockProtection::decode(0xff, 0x00));
+        assert_eq!(state.is_writable(0x1ff), Some(false));
+        assert_eq!(state.is_writable(0x200), Some(true));
+        assert_eq!(state.audit(), Err(AuditError::Unprotected(8)));
+
+        match state.change_to(0xffff).unwrap() {
+            ProtectionChange::Spd5118(seq) => {
+                assert!(!seq.requires_offline());
+                assert_eq!(seq.target(), BlockProtection::ALL);
+            }
+            change => panic!("unexpected change {:?}", change),
+        }
+
+        match state.change_to(0x00ff ^ 1).unwrap() {
+            ProtectionChange::Spd5118(seq) => assert!(seq.requires_offline()),
+            change => panic!("unexpected change {:?}", change),
+        }
+
+        state.record(BlockProtection::ALL);
+        assert_eq!(state.audit(), Ok(()));
+
+        //
+        // Everything but the end-user region may be locked down.
+        //
+        let end_user = state.blocks_of(Region::EndUser);
+        assert_eq!(end_user, 0xfc00);
+        assert_eq!(state.blocks_of(Region::Manufacturing), 0x0300);
+        assert!(matches!(
+            state.change_to(!end_user),
+            Some(ProtectionChange::Spd5118(_))
+        ));
+
+        let state = ProtectionState::new(DeviceType::Ddr4);
+        assert_eq!(state.blocks_of(Region::EndUser), 0b1000);
+        assert_eq!(state.blocks_of(Region::Manufacturing), 0b0100);
+    }
+}
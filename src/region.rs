@@ -0,0 +1,209 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{DeviceType, DDR5_MAX_SIZE, MAX_SIZE};
+use core::ops::RangeInclusive;
+
+///
+/// The region of an image to which a byte belongs, which determines who
+/// owns its contents.  A CRC belongs to the region containing it.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Region {
+    /// Base configuration and DRAM parameters.
+    BaseConfiguration,
+    /// Parameters specific to the module type (RDIMM, UDIMM, etc.).
+    ModuleSpecific,
+    /// Parameters specific to hybrid (e.g. NVDIMM) modules.
+    Hybrid,
+    /// Manufacturing information:  identity, date, serial and part number,
+    /// and manufacturer-specific data.
+    Manufacturing,
+    /// Programmable by the end user.
+    EndUser,
+    /// Not assigned to any region.
+    Reserved,
+}
+
+///
+/// The regions of each generation, as (limit, region), in ascending order
+/// and together covering the image.
+///
+const DDR4_REGIONS: &[(usize, Region)] = &[
+    (0x07f, Region::BaseConfiguration),
+    (0x0bf, Region::ModuleSpecific),
+    (0x0ff, Region::Hybrid),
+    (0x13f, Region::Reserved),
+    (0x17d, Region::Manufacturing),
+    (0x17f, Region::Reserved),
+    (MAX_SIZE - 1, Region::EndUser),
+];
+
+///
+/// DDR5 has no hybrid parameters; its single CRC closes the module-specific
+/// section, after a reserved gap.
+///
+const DDR5_REGIONS: &[(usize, Region)] = &[
+    (0x07f, Region::BaseConfiguration),
+    (0x0bf, Region::Reserved),
+    (0x1bf, Region::ModuleSpecific),
+    (0x1fd, Region::Reserved),
+    (0x1ff, Region::ModuleSpecific),
+    (0x27f, Region::Manufacturing),
+    (DDR5_MAX_SIZE - 1, Region::EndUser),
+];
+
+fn regions_of(device: DeviceType) -> &'static [(usize, Region)] {
+    match device {
+        DeviceType::Ddr4 | DeviceType::Ddr4E => DDR4_REGIONS,
+        DeviceType::Ddr5 => DDR5_REGIONS,
+    }
+}
+
+///
+/// The region containing the byte at the given offset of an image of the
+/// given device type, or `None` if the offset is beyond the end of the
+/// image.
+///
+pub fn classify(device: DeviceType, offset: usize) -> Option<Region> {
+    regions_of(device)
+        .iter()
+        .find(|&&(limit, _)| offset <= limit)
+        .map(|&(_, region)| region)
+}
+
+///
+/// Iterate over the contiguous ranges of an image of the given device type
+/// and the region of each, in ascending order.  A region may appear more
+/// than once.
+///
+pub fn regions(
+    device: DeviceType,
+) -> impl Iterator<Item = (RangeInclusive<usize>, Region)> {
+    let regions = regions_of(device);
+
+    regions
+        .iter()
+        .enumerate()
+        .map(move |(i, &(limit, region))| {
+            let base = if i == 0 { 0 } else { regions[i - 1].0 + 1 };
+            (base..=limit, region)
+        })
+}
+
+impl Region {
+    ///
+    /// Returns true if the region is expected to differ between modules of
+    /// the same SKU; the per-unit identity fields are not included.
+    ///
+    pub fn is_per_unit(self) -> bool {
+        self == Region::EndUser
+    }
+
+    ///
+    /// Iterate over the offsets of every byte of this region in an image of
+    /// the given device type.
+    ///
+    pub fn offsets(self, device: DeviceType) -> impl Iterator<Item = usize> {
+        regions(device)
+            .filter(move |&(_, region)| region == self)
+            .flat_map(|(range, _)| range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ddr5, Area, ByteClass, ByteMap, Offset};
+
+    #[test]
+    fn ddr4() {
+        let device = DeviceType::Ddr4;
+        let region = |offset| classify(device, offset);
+
+        assert_eq!(region(0), Some(Region::BaseConfiguration));
+        assert_eq!(region(0x07e), Some(Region::BaseConfiguration));
+        assert_eq!(region(0x080), Some(Region::ModuleSpecific));
+        assert_eq!(region(0x0c0), Some(Region::Hybrid));
+        assert_eq!(region(0x0fe), Some(Region::Hybrid));
+        assert_eq!(region(0x100), Some(Region::Reserved));
+        assert_eq!(
+            region(Offset::ModuleSerialNumber0.to_usize()),
+            Some(Region::Manufacturing)
+        );
+        assert_eq!(region(0x17e), Some(Region::Reserved));
+        assert_eq!(region(0x180), Some(Region::EndUser));
+        assert_eq!(region(MAX_SIZE), None);
+        assert_eq!(classify(DeviceType::Ddr4E, 0x1ff), Some(Region::EndUser));
+
+        assert_eq!(Region::EndUser.offsets(device).count(), 0x80);
+        assert_eq!(Region::Manufacturing.offsets(device).count(), 0x3e);
+        assert_eq!(regions(device).last().unwrap().0.end() + 1, MAX_SIZE);
+    }
+
+    #[test]
+    fn ddr5() {
+        let device = DeviceType::Ddr5;
+
+        assert_eq!(classify(device, 0x0c0), Some(Region::ModuleSpecific));
+        assert_eq!(classify(device, 0x1c0), Some(Region::Reserved));
+        assert_eq!(classify(device, 0x1fe), Some(Region::ModuleSpecific));
+        assert_eq!(Region::Hybrid.offsets(device).count(), 0);
+        assert_eq!(
+            classify(device, ddr5::Offset::PartNumberBase.to_usize()),
+            Some(Region::Manufacturing)
+        );
+        assert_eq!(classify(device, 0x280), Some(Region::EndUser));
+        assert_eq!(classify(device, DDR5_MAX_SIZE), None);
+        assert_eq!(regions(device).last().unwrap().0.end() + 1, DDR5_MAX_SIZE);
+    }
+
+    #[test]
+    fn consistent() {
+        //
+        // Bytes in no region are reserved as far as the byte map is
+        // concerned, the end-user areas are vendor-specific, and nothing
+        // else is vendor-specific but manufacturer data.
+        //
+        for &device in [DeviceType::Ddr4, DeviceType::Ddr5].iter() {
+            let map = ByteMap::for_device(device);
+
+            for (range, region) in regions(device) {
+                for o in range {
+                    let class = map.class(o).unwrap();
+
+                    let consistent = match region {
+                        Region::Reserved => class == ByteClass::Reserved,
+                        Region::EndUser => class == ByteClass::VendorSpecific,
+                        Region::Manufacturing => class != ByteClass::Reserved,
+                        Region::BaseConfiguration
+                        | Region::ModuleSpecific
+                        | Region::Hybrid => class != ByteClass::VendorSpecific,
+                    };
+
+                    assert!(consistent, "{:?} 0x{:03x}", device, o);
+                }
+            }
+        }
+
+        //
+        // The DDR4 manufacturer and end-user areas together make up the
+        // end-user region.
+        //
+        let device = DeviceType::Ddr4;
+        let areas = [Area::Manufacturer, Area::EndUser];
+
+        assert!(areas.iter().all(|area| (area.base()..=area.limit())
+            .all(|o| classify(device, o) == Some(Region::EndUser))));
+        assert_eq!(
+            areas
+                .iter()
+                .map(|a| a.limit() + 1 - a.base())
+                .sum::<usize>(),
+            Region::EndUser.offsets(device).count()
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::summary::Identity;
+use crate::{ddr5, update_crc, DeviceType, Error};
+
+///
+/// The per-unit identity fields that are reprogrammed when a module is
+/// relabeled.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Relabel {
+    pub serial: u32,
+    /// Manufacturing year and week, in BCD.
+    pub year: u8,
+    pub week: u8,
+    pub location: u8,
+}
+
+impl Relabel {
+    ///
+    /// Write the identity into the manufacturing information of an image in
+    /// place, then recompute its CRCs.
+    ///
+    pub fn apply(&self, buf: &mut [u8]) -> Result<(), Error> {
+        let device_type =
+            DeviceType::from_spd(buf).ok_or(Error::TypeMismatch)?;
+        let identity = Identity::of(device_type);
+
+        let len = match device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => crate::MAX_SIZE,
+            DeviceType::Ddr5 => crate::DDR5_MAX_SIZE,
+        };
+
+        if buf.len() < len {
+            return Err(Error::BufferTooSmall);
+        }
+
+        buf[identity.location] = self.location;
+        buf[identity.year] = self.year;
+        buf[identity.year + 1] = self.week;
+
+        let s = identity.serial;
+        buf[s..s + 4].copy_from_slice(&self.serial.to_be_bytes());
+
+        match device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => update_crc(buf),
+            DeviceType::Ddr5 => ddr5::update_crc(buf),
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// Return a copy of an image carrying a new identity, with all CRCs
+/// recomputed.
+///
+pub fn reserialize<const N: usize>(
+    image: &[u8; N],
+    relabel: &Relabel,
+) -> Result<[u8; N], Error> {
+    let mut image = *image;
+    relabel.apply(&mut image)?;
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{verify_crc, Summary, DDR5_MAX_SIZE, MAX_SIZE};
+
+    const RELABEL: Relabel = Relabel {
+        serial: 0x1234_5678,
+        year: 0x24,
+        week: 0x11,
+        location: 0x03,
+    };
+
+    #[test]
+    fn ddr4() {
+        let mut image = [0u8; MAX_SIZE];
+        image[0x002] = 0x0c;
+        image[0x145] = 0xff;
+        update_crc(&mut image);
+
+        let relabeled = reserialize(&image, &RELABEL).unwrap();
+        assert!(verify_crc(&relabeled));
+        assert_eq!(
+            relabeled[0x142..0x149],
+            [0x03, 0x24, 0x11, 0x12, 0x34, 0x56, 0x78]
+        );
+
+        let summary = Summary::from_spd(&relabeled).unwrap();
+        assert_eq!(summary.serial, 0x1234_5678);
+        assert_eq!((summary.year, summary.week), (0x24, 0x11));
+
+        assert_eq!(
+            RELABEL.apply(&mut image[..256]),
+            Err(Error::BufferTooSmall)
+        );
+        assert_eq!(reserialize(&[0u8; 4], &RELABEL), Err(Error::TypeMismatch));
+    }
+
+    #[test]
+    fn ddr5() {
+        let image = ddr5::Builder::new(0x01).serial(1).build().unwrap();
+        let relabeled = reserialize(&image, &RELABEL).unwrap();
+
+        assert_eq!(relabeled[0x202], 0x03);
+        assert_eq!(relabeled[0x205..0x209], [0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(crate::validate_spd(&relabeled), Ok(()));
+        assert_eq!(relabeled.len(), DDR5_MAX_SIZE);
+    }
+}
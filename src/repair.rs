@@ -0,0 +1,140 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{DeviceType, Offset};
+
+///
+/// Post package repair support, from bits 7:6 of byte 9.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PostPackageRepair {
+    Unsupported,
+    /// One row may be repaired in each bank group.
+    OneRowPerBankGroup,
+    /// A reserved encoding.
+    Reserved(u8),
+}
+
+///
+/// The maximum activate count (MAC), from bits 3:0 of byte 7:  the number
+/// of activates a row may receive within the maximum activate window before
+/// adjacent rows must be refreshed.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MaximumActivateCount {
+    Untested,
+    Activations(u32),
+    Unlimited,
+    /// A reserved encoding.
+    Reserved(u8),
+}
+
+impl MaximumActivateCount {
+    fn decode(mac: u8) -> Self {
+        match mac {
+            0b0000 => MaximumActivateCount::Untested,
+            0b0001..=0b0110 => MaximumActivateCount::Activations(
+                800_000 - mac as u32 * 100_000,
+            ),
+            0b1000 => MaximumActivateCount::Unlimited,
+            _ => MaximumActivateCount::Reserved(mac),
+        }
+    }
+}
+
+///
+/// The repair and row hammer related capabilities of a DDR4 module, for
+/// use by RAS policy.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RepairCapabilities {
+    pub ppr: PostPackageRepair,
+    /// Soft (volatile) post package repair is supported.
+    pub soft_ppr: bool,
+    pub mac: MaximumActivateCount,
+    /// The maximum activate window, in multiples of tREFI, if the encoding
+    /// is not reserved.
+    pub tmaw_trefi: Option<u16>,
+}
+
+impl RepairCapabilities {
+    ///
+    /// Decode the capabilities of a DDR4 image.  Returns `None` if the image
+    /// is not DDR4 or is too short.
+    ///
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        if DeviceType::from_spd(buf)?.layout() != DeviceType::Ddr4
+            || buf.len() <= Offset::OtherSDRAMFeatures.to_usize()
+        {
+            return None;
+        }
+
+        let optional = Offset::SDRAMOptionalFeatures.within(buf);
+        let other = Offset::OtherSDRAMFeatures.within(buf);
+
+        Some(Self {
+            ppr: match other >> 6 {
+                0b00 => PostPackageRepair::Unsupported,
+                0b01 => PostPackageRepair::OneRowPerBankGroup,
+                ppr => PostPackageRepair::Reserved(ppr),
+            },
+            soft_ppr: other & (1 << 5) != 0,
+            mac: MaximumActivateCount::decode(optional & 0b1111),
+            tmaw_trefi: match (optional >> 4) & 0b11 {
+                0b00 => Some(8192),
+                0b01 => Some(4096),
+                0b10 => Some(2048),
+                _ => None,
+            },
+        })
+    }
+
+    ///
+    /// Returns true if a failing row can be repaired, whether persistently
+    /// or not.
+    ///
+    pub fn can_repair(&self) -> bool {
+        self.ppr == PostPackageRepair::OneRowPerBankGroup || self.soft_ppr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    #[test]
+    fn decode() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+
+        let caps = RepairCapabilities::from_spd(&buf).unwrap();
+        assert_eq!(caps.ppr, PostPackageRepair::Unsupported);
+        assert_eq!(caps.mac, MaximumActivateCount::Untested);
+        assert_eq!(caps.tmaw_trefi, Some(8192));
+        assert!(!caps.can_repair());
+
+        buf[0x007] = 0b0000_1000;
+        buf[0x009] = 0b0110_0000;
+        let caps = RepairCapabilities::from_spd(&buf).unwrap();
+        assert_eq!(caps.ppr, PostPackageRepair::OneRowPerBankGroup);
+        assert!(caps.soft_ppr && caps.can_repair());
+        assert_eq!(caps.mac, MaximumActivateCount::Unlimited);
+
+        buf[0x007] = 0b0010_0011;
+        buf[0x009] = 0b1000_0000;
+        let caps = RepairCapabilities::from_spd(&buf).unwrap();
+        assert_eq!(caps.ppr, PostPackageRepair::Reserved(0b10));
+        assert!(!caps.can_repair());
+        assert_eq!(caps.mac, MaximumActivateCount::Activations(500_000));
+        assert_eq!(caps.tmaw_trefi, Some(2048));
+
+        buf[0x002] = 0x12;
+        assert_eq!(RepairCapabilities::from_spd(&buf), None);
+        assert_eq!(RepairCapabilities::from_spd(&buf[..4]), None);
+        assert_eq!(RepairCapabilities::from_spd(&[0; 2]), None);
+    }
+}
@@ -0,0 +1,300 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+//!
+//! Mapping of a decoded SPD to the fields of an SMBIOS Type 17 (Memory
+//! Device) structure, as described in DSP0134, so that host firmware need
+//! not maintain its own copy of the decode.
+//!
+
+use crate::{
+    manufacturer_name, DeviceType, HybridMedia, Offset, Picoseconds, Summary,
+};
+
+///
+/// Type detail bits.
+///
+pub const TYPE_DETAIL_SYNCHRONOUS: u16 = 1 << 7;
+pub const TYPE_DETAIL_REGISTERED: u16 = 1 << 13;
+pub const TYPE_DETAIL_UNBUFFERED: u16 = 1 << 14;
+pub const TYPE_DETAIL_LRDIMM: u16 = 1 << 15;
+
+///
+/// The JEDEC speed bins, in MT/s.  A module's tCKmin is rounded, so its
+/// speed is taken to be that of the fastest bin it supports.
+///
+const SPEED_BINS: &[u32] = &[
+    1600, 1866, 2133, 2400, 2666, 2933, 3200, 3600, 4000, 4400, 4800, 5200,
+    5600, 6000, 6400, 6800, 7200, 7600, 8000, 8400, 8800,
+];
+
+///
+/// The size, in MiB, above which the extended size field is used.
+///
+const EXTENDED_SIZE: u64 = 0x7fff;
+
+///
+/// The Memory Type field.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MemoryType {
+    Ddr4 = 0x1a,
+    Ddr5 = 0x22,
+}
+
+///
+/// The Form Factor field.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FormFactor {
+    Other = 0x01,
+    Dimm = 0x09,
+    SoDimm = 0x0d,
+}
+
+///
+/// The Memory Technology field.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MemoryTechnology {
+    Other = 0x01,
+    Dram = 0x03,
+    NvdimmN = 0x04,
+    NvdimmP = 0x06,
+}
+
+///
+/// The Type 17 fields that are derived from the SPD.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Type17 {
+    pub memory_type: MemoryType,
+    pub form_factor: FormFactor,
+    pub type_detail: u16,
+    pub memory_technology: MemoryTechnology,
+    /// The speed, in MT/s, if tCKmin is set.
+    pub speed: Option<u32>,
+    /// The module manufacturer, if known.
+    pub manufacturer: Option<&'static str>,
+    /// The serial number, as eight hexadecimal digits.
+    pub serial_number: [u8; 8],
+    /// Package ranks (per channel, for DDR5), if they could be determined.
+    pub rank: Option<u8>,
+    /// The size in MiB, if it could be determined.
+    pub size_mib: Option<u64>,
+    summary: Summary,
+}
+
+///
+/// The speed of the fastest bin whose tCK is no shorter than `tck`, or the
+/// speed corresponding to `tck` if it is beyond the known bins.
+///
+fn speed(tck: Picoseconds) -> Option<u32> {
+    if tck.0 == 0 {
+        return None;
+    }
+
+    let bin = |rate: u32| 2_000_000u32.div_ceil(rate);
+    let fastest = *SPEED_BINS.last().unwrap();
+
+    if tck.0 < bin(fastest) {
+        return Some(2_000_000 / tck.0);
+    }
+
+    Some(
+        SPEED_BINS
+            .iter()
+            .rev()
+            .copied()
+            .find(|&rate| bin(rate) >= tck.0)
+            .unwrap_or(2_000_000 / tck.0),
+    )
+}
+
+impl Type17 {
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        let summary = Summary::from_spd(buf)?;
+
+        //
+        // The base module type is in the low nibble of byte 3 for both
+        // generations, with RDIMM, UDIMM, SO-DIMM and LRDIMM in common.
+        // Beyond those, DDR4 has the mini and 72-bit SO-DIMM variants where
+        // DDR5 has the clocked UDIMM and SO-DIMM.
+        //
+        let ddr4 = summary.device_type.layout() == DeviceType::Ddr4;
+
+        let (form_factor, buffering) = match summary.module_type & 0xf {
+            0x1 => (FormFactor::Dimm, TYPE_DETAIL_REGISTERED),
+            0x2 => (FormFactor::Dimm, TYPE_DETAIL_UNBUFFERED),
+            0x3 => (FormFactor::SoDimm, TYPE_DETAIL_UNBUFFERED),
+            0x4 => (FormFactor::Dimm, TYPE_DETAIL_LRDIMM),
+            0x5 if ddr4 => (FormFactor::Dimm, TYPE_DETAIL_REGISTERED),
+            0x6 if ddr4 => (FormFactor::Dimm, TYPE_DETAIL_UNBUFFERED),
+            0x5 => (FormFactor::Dimm, TYPE_DETAIL_UNBUFFERED),
+            0x6 => (FormFactor::SoDimm, TYPE_DETAIL_UNBUFFERED),
+            0x8 if ddr4 => (FormFactor::SoDimm, TYPE_DETAIL_REGISTERED),
+            0x9 if ddr4 => (FormFactor::SoDimm, TYPE_DETAIL_UNBUFFERED),
+            _ => (FormFactor::Other, 0),
+        };
+
+        let memory_technology = match HybridMedia::decode(
+            summary.device_type,
+            Offset::ModuleType.within(buf),
+        ) {
+            None => MemoryTechnology::Dram,
+            Some(HybridMedia::NvdimmN) => MemoryTechnology::NvdimmN,
+            Some(HybridMedia::NvdimmP) => MemoryTechnology::NvdimmP,
+            Some(HybridMedia::Reserved(_)) => MemoryTechnology::Other,
+        };
+
+        let mut serial_number = [0u8; 8];
+        for (i, digit) in serial_number.iter_mut().enumerate() {
+            let nibble = (summary.serial >> (28 - i * 4)) & 0xf;
+            *digit = b"0123456789ABCDEF"[nibble as usize];
+        }
+
+        Some(Self {
+            memory_type: match summary.device_type.layout() {
+                DeviceType::Ddr5 => MemoryType::Ddr5,
+                _ => MemoryType::Ddr4,
+            },
+            form_factor,
+            type_detail: TYPE_DETAIL_SYNCHRONOUS | buffering,
+            memory_technology,
+            speed: speed(summary.tck_min),
+            manufacturer: manufacturer_name(summary.module_manufacturer),
+            serial_number,
+            rank: summary.ranks,
+            size_mib: summary.capacity.map(|c| c >> 20),
+            summary,
+        })
+    }
+
+    ///
+    /// The part number with trailing padding removed.
+    ///
+    pub fn part_number(&self) -> &[u8] {
+        self.summary.part_number()
+    }
+
+    pub fn serial_number(&self) -> &str {
+        core::str::from_utf8(&self.serial_number).unwrap()
+    }
+
+    ///
+    /// The Size and Extended Size fields:  0xFFFF if the size is unknown,
+    /// or 0x7FFF if it is given by the extended size (in MiB).
+    ///
+    pub fn size_fields(&self) -> (u16, u32) {
+        match self.size_mib {
+            None => (0xffff, 0),
+            Some(mib) if mib < EXTENDED_SIZE => (mib as u16, 0),
+            Some(mib) => (EXTENDED_SIZE as u16, mib.min(0x7fff_ffff) as u32),
+        }
+    }
+
+    ///
+    /// The Speed and Extended Speed fields:  0 if the speed is unknown, or
+    /// 0xFFFF if it is given by the extended speed.
+    ///
+    pub fn speed_fields(&self) -> (u16, u32) {
+        match self.speed {
+            None => (0, 0),
+            Some(speed) if speed < 0xffff => (speed as u16, 0),
+            Some(speed) => (0xffff, speed),
+        }
+    }
+
+    ///
+    /// The Attributes field, which holds the rank (0 if unknown).
+    ///
+    pub fn attributes(&self) -> u8 {
+        self.rank.unwrap_or(0) & 0xf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ddr5, update_crc, MAX_SIZE};
+
+    #[test]
+    fn ddr4() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x003] = 0x01;
+        buf[0x004] = 0x85;
+        buf[0x00c] = 0x08;
+        buf[0x00d] = 0x0b;
+        buf[0x012] = 0x05;
+        buf[0x140] = 0x80;
+        buf[0x141] = 0xce;
+        buf[0x145..0x149].copy_from_slice(&[0x01, 0x2a, 0xbc, 0xde]);
+        buf[0x149..0x15d].copy_from_slice(b"M393A4K40DB3-CWE    ");
+        update_crc(&mut buf);
+
+        let t17 = Type17::from_spd(&buf).unwrap();
+        assert_eq!(t17.memory_type as u8, 0x1a);
+        assert_eq!(t17.form_factor, FormFactor::Dimm);
+        assert_eq!(t17.type_detail, 0x2080);
+        assert_eq!(t17.memory_technology, MemoryTechnology::Dram);
+        assert_eq!(t17.speed_fields(), (3200, 0));
+        assert_eq!(t17.manufacturer, Some("Samsung"));
+        assert_eq!(t17.serial_number(), "012ABCDE");
+        assert_eq!(t17.part_number(), b"M393A4K40DB3-CWE");
+        assert_eq!(t17.attributes(), 2);
+        assert_eq!(t17.size_fields(), (0x7fff, 32 * 1024));
+
+        //
+        // An NVDIMM-N SO-DIMM at DDR4-2933, whose tCKmin is rounded to
+        // 682 ps.
+        //
+        buf[0x003] = 0x93;
+        buf[0x012] = 0x06;
+        buf[0x07d] = 0xb5;
+        let t17 = Type17::from_spd(&buf).unwrap();
+        assert_eq!(t17.form_factor, FormFactor::SoDimm);
+        assert_eq!(t17.memory_technology, MemoryTechnology::NvdimmN);
+        assert_eq!(t17.speed, Some(2933));
+
+        assert_eq!(Type17::from_spd(&buf[..4]), None);
+    }
+
+    #[test]
+    fn ddr5() {
+        let image = ddr5::Builder::new(0x03)
+            .timing(ddr5::Timing::TCkAvgMin, Picoseconds(416))
+            .serial(0xff)
+            .build()
+            .unwrap();
+
+        let t17 = Type17::from_spd(&image).unwrap();
+        assert_eq!(t17.memory_type, MemoryType::Ddr5);
+        assert_eq!(t17.form_factor, FormFactor::SoDimm);
+        assert_eq!(t17.speed, Some(4800));
+        assert_eq!(t17.serial_number(), "000000FF");
+        assert_eq!(t17.manufacturer, None);
+        assert_eq!(t17.size_fields(), (0xffff, 0));
+        assert_eq!(t17.attributes(), 0);
+
+        //
+        // A CUDIMM is an unbuffered DIMM, and a CSODIMM an unbuffered
+        // SO-DIMM, not the DDR4 mini-RDIMM and mini-UDIMM.
+        //
+        let cudimm = ddr5::Builder::new(0x05).build().unwrap();
+        let t17 = Type17::from_spd(&cudimm).unwrap();
+        assert_eq!(t17.form_factor, FormFactor::Dimm);
+        assert_eq!(t17.type_detail, 0x4080);
+
+        let csodimm = ddr5::Builder::new(0x06).build().unwrap();
+        let t17 = Type17::from_spd(&csodimm).unwrap();
+        assert_eq!(t17.form_factor, FormFactor::SoDimm);
+        assert_eq!(t17.type_detail, 0x4080);
+
+        assert_eq!(speed(Picoseconds(200)), Some(10000));
+        assert_eq!(speed(Picoseconds(0)), None);
+    }
+}
@@ -0,0 +1,284 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::summary::trim;
+use crate::{
+    classify, is_valid_jep106, validate_spd, ByteMap, GenericView, KeyByte,
+    ModuleIdentity, Region, Summary, ValidationError, PART_NUMBER_LEN,
+};
+
+///
+/// How tolerant decoding is of a damaged or nonconforming image.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Strictness {
+    /// Reject any validation failure or nonzero reserved byte, as wanted
+    /// when booting production systems.
+    Strict,
+    /// Decode anything of a known device type, reporting problems as
+    /// warnings.
+    Permissive,
+    /// Extract whatever identity fields are still plausible, whatever the
+    /// state of the image, as wanted for failure analysis.
+    Salvage,
+}
+
+///
+/// Why an image was rejected.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DecodeError {
+    Invalid(ValidationError),
+    /// The reserved byte at the given offset, in the given region, is not
+    /// zero.
+    Reserved(usize, Region),
+}
+
+///
+/// The problems found in an image that was nonetheless decoded.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Warnings {
+    pub validation: Option<ValidationError>,
+    /// The offset and region of the first nonzero reserved byte.
+    pub reserved: Option<(usize, Region)>,
+}
+
+impl Warnings {
+    pub fn is_empty(&self) -> bool {
+        self.validation.is_none() && self.reserved.is_none()
+    }
+}
+
+///
+/// The identity fields of a module, each of which is `None` if it was
+/// found to be implausible when salvaging.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SalvagedIdentity {
+    pub module_manufacturer: Option<u16>,
+    pub dram_manufacturer: Option<u16>,
+    pub serial: Option<u32>,
+    /// Manufacturing year and week, in BCD.
+    pub date: Option<(u8, u8)>,
+    pub part_number: Option<[u8; PART_NUMBER_LEN]>,
+}
+
+fn is_bcd(value: u8) -> bool {
+    value >> 4 < 10 && value & 0xf < 10
+}
+
+impl SalvagedIdentity {
+    fn all(identity: &ModuleIdentity) -> Self {
+        Self {
+            module_manufacturer: Some(identity.module_manufacturer),
+            dram_manufacturer: Some(identity.dram_manufacturer),
+            serial: Some(identity.serial),
+            date: Some((identity.year, identity.week)),
+            part_number: Some(identity.part_number),
+        }
+    }
+
+    ///
+    /// Keep only the fields that could have been programmed deliberately:
+    /// well-formed JEP106 IDs, a serial number that is not all zeros or all
+    /// ones, a BCD date, and a part number of printable ASCII.
+    ///
+    fn plausible(identity: &ModuleIdentity) -> Self {
+        let part_number = trim(&identity.part_number);
+
+        Self {
+            module_manufacturer: Some(identity.module_manufacturer)
+                .filter(|&id| is_valid_jep106(id)),
+            dram_manufacturer: Some(identity.dram_manufacturer)
+                .filter(|&id| is_valid_jep106(id)),
+            serial: Some(identity.serial).filter(|&s| s != 0 && s != u32::MAX),
+            date: Some((identity.year, identity.week)).filter(|&(y, w)| {
+                is_bcd(y) && is_bcd(w) && (0x01..=0x53).contains(&w)
+            }),
+            part_number: Some(identity.part_number).filter(|_| {
+                !part_number.is_empty()
+                    && part_number.iter().all(|&b| (0x20..=0x7e).contains(&b))
+            }),
+        }
+    }
+
+    ///
+    /// The part number with trailing padding removed.
+    ///
+    pub fn part_number(&self) -> Option<&[u8]> {
+        self.part_number.as_ref().map(trim)
+    }
+}
+
+///
+/// The result of decoding at a given strictness.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Decoded {
+    /// The summary, unless salvaging an image that fails validation.
+    pub summary: Option<Summary>,
+    pub identity: SalvagedIdentity,
+    /// Always empty when decoding strictly.
+    pub warnings: Warnings,
+}
+
+impl Strictness {
+    pub fn decode(self, buf: &[u8]) -> Result<Decoded, DecodeError> {
+        let length = DecodeError::Invalid(ValidationError::Length);
+        let view = GenericView::new(buf).ok_or(length)?;
+
+        let warnings = Warnings {
+            validation: validate_spd(buf).err(),
+            reserved: match view.key() {
+                KeyByte::Known(device_type) => ByteMap::for_device(device_type)
+                    .reserved_violations(buf)
+                    .find_map(|o| Some((o, classify(device_type, o)?))),
+                KeyByte::Unknown(_) => None,
+            },
+        };
+
+        if self == Strictness::Salvage {
+            let identity = view.identity().ok_or(length)?;
+
+            return Ok(Decoded {
+                summary: match warnings.validation {
+                    None => Summary::from_spd(buf),
+                    Some(_) => None,
+                },
+                identity: SalvagedIdentity::plausible(&identity),
+                warnings,
+            });
+        }
+
+        if self == Strictness::Strict {
+            if let Some(err) = warnings.validation {
+                return Err(DecodeError::Invalid(err));
+            }
+
+            if let Some((offset, region)) = warnings.reserved {
+                return Err(DecodeError::Reserved(offset, region));
+            }
+        }
+
+        let summary = Summary::from_spd(buf).ok_or(DecodeError::Invalid(
+            warnings.validation.unwrap_or(ValidationError::Length),
+        ))?;
+        let identity = view.identity().ok_or(length)?;
+
+        Ok(Decoded {
+            summary: Some(summary),
+            identity: SalvagedIdentity::all(&identity),
+            warnings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{update_crc, MAX_SIZE};
+
+    fn image() -> [u8; MAX_SIZE] {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x003] = 0x01;
+        buf[0x140] = 0x80;
+        buf[0x141] = 0xce;
+        buf[0x143..0x149]
+            .copy_from_slice(&[0x21, 0x13, 0x01, 0x02, 0x03, 0x04]);
+        buf[0x149..0x15d].copy_from_slice(b"M393A4K40DB3-CWE    ");
+        update_crc(&mut buf);
+        buf
+    }
+
+    #[test]
+    fn strict() {
+        let buf = image();
+        let decoded = Strictness::Strict.decode(&buf).unwrap();
+        assert!(decoded.warnings.is_empty());
+        assert_eq!(decoded.identity.serial, Some(0x0102_0304));
+        assert_eq!(decoded.summary.unwrap().crc_valid, Some(true));
+
+        let mut corrupt = buf;
+        corrupt[0x003] = 0x02;
+        assert_eq!(
+            Strictness::Strict.decode(&corrupt),
+            Err(DecodeError::Invalid(ValidationError::Crc))
+        );
+
+        let decoded = Strictness::Permissive.decode(&corrupt).unwrap();
+        assert_eq!(decoded.warnings.validation, Some(ValidationError::Crc));
+        assert_eq!(decoded.summary.unwrap().module_type, 0x02);
+
+        let mut reserved = buf;
+        reserved[0x050] = 0x01;
+        update_crc(&mut reserved);
+        assert_eq!(
+            Strictness::Strict.decode(&reserved),
+            Err(DecodeError::Reserved(0x050, Region::BaseConfiguration))
+        );
+        assert_eq!(
+            Strictness::Permissive.decode(&reserved).unwrap().warnings,
+            Warnings {
+                validation: None,
+                reserved: Some((0x050, Region::BaseConfiguration)),
+            }
+        );
+
+        let mut reserved = buf;
+        reserved[0x100] = 0x01;
+        assert_eq!(
+            Strictness::Strict.decode(&reserved),
+            Err(DecodeError::Reserved(0x100, Region::Reserved))
+        );
+
+        assert_eq!(
+            Strictness::Permissive.decode(&buf[..256]),
+            Err(DecodeError::Invalid(ValidationError::Length))
+        );
+        assert_eq!(
+            Strictness::Salvage.decode(&[]),
+            Err(DecodeError::Invalid(ValidationError::Length))
+        );
+    }
+
+    #[test]
+    fn salvage() {
+        let mut buf = image();
+        buf[0x002] = 0xff;
+        buf[0x141] = 0x4e;
+        buf[0x144] = 0x60;
+        buf[0x145..0x149].fill(0xff);
+
+        assert_eq!(
+            Strictness::Permissive.decode(&buf),
+            Err(DecodeError::Invalid(ValidationError::DeviceType))
+        );
+
+        let decoded = Strictness::Salvage.decode(&buf).unwrap();
+        assert_eq!(decoded.summary, None);
+        assert_eq!(
+            decoded.warnings.validation,
+            Some(ValidationError::DeviceType)
+        );
+
+        let identity = decoded.identity;
+        assert_eq!(identity.module_manufacturer, None);
+        assert_eq!(identity.serial, None);
+        assert_eq!(identity.date, None);
+        assert_eq!(identity.part_number(), Some(&b"M393A4K40DB3-CWE"[..]));
+
+        buf[0x14a] = 0x00;
+        let identity = Strictness::Salvage.decode(&buf).unwrap().identity;
+        assert_eq!(identity.part_number, None);
+
+        let decoded = Strictness::Salvage.decode(&image()).unwrap();
+        assert!(decoded.summary.is_some());
+        assert_eq!(decoded.identity.date, Some((0x21, 0x13)));
+        assert_eq!(decoded.identity.module_manufacturer, Some(0xce80));
+    }
+}
@@ -0,0 +1,286 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{
+    ddr5, verify_crc, DeviceType, Offset, Organization, Picoseconds, Timing,
+};
+
+///
+/// The maximum length of a part number (DDR5; DDR4 part numbers are 20
+/// bytes).
+///
+pub const PART_NUMBER_LEN: usize = 30;
+
+///
+/// A summary of the identity and headline characteristics of a module,
+/// decoded from either a DDR4 or DDR5 SPD image.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub device_type: DeviceType,
+    /// The module type byte (byte 3).
+    pub module_type: u8,
+    /// JEP106 manufacturer ID of the module, continuation count in the low
+    /// byte.
+    pub module_manufacturer: u16,
+    /// JEP106 manufacturer ID of the DRAM.
+    pub dram_manufacturer: u16,
+    pub serial: u32,
+    /// Manufacturing year and week, in BCD.
+    pub year: u8,
+    pub week: u8,
+    /// Part number, space padded.
+    pub part_number: [u8; PART_NUMBER_LEN],
+    pub tck_min: Picoseconds,
+    pub taa_min: Picoseconds,
+    /// Capacity in bytes, if it could be determined.
+    pub capacity: Option<u64>,
+    /// Package ranks (per channel, for DDR5), if they could be determined.
+    pub ranks: Option<u8>,
+    /// Whether the CRCs are valid, if they were checked.
+    pub crc_valid: Option<bool>,
+}
+
+///
+/// The offsets of the identity fields, which are laid out identically in
+/// DDR4 and DDR5 but at different locations.
+///
+pub(crate) struct Identity {
+    pub manufacturer: usize,
+    pub location: usize,
+    pub year: usize,
+    pub serial: usize,
+    pub part_number: usize,
+    pub part_number_len: usize,
+    pub revision: usize,
+    pub dram_manufacturer: usize,
+    pub dram_stepping: usize,
+}
+
+impl Identity {
+    pub fn of(device_type: DeviceType) -> Self {
+        match device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => Identity {
+                manufacturer: Offset::ModuleManufacturerIDCodeLSB.to_usize(),
+                location: Offset::ModuleManufacturingLocation.to_usize(),
+                year: Offset::ModuleManufacturingDateYear.to_usize(),
+                serial: Offset::ModuleSerialNumber0.to_usize(),
+                part_number: Offset::PartNumberBase.to_usize(),
+                part_number_len: Offset::PartNumberLimit.to_usize()
+                    - Offset::PartNumberBase.to_usize()
+                    + 1,
+                revision: Offset::ModuleRevisionCode.to_usize(),
+                dram_manufacturer: Offset::DRAMManufacturerIDCodeLSB.to_usize(),
+                dram_stepping: Offset::DRAMStepping.to_usize(),
+            },
+            DeviceType::Ddr5 => Identity {
+                manufacturer: ddr5::Offset::ModuleManufacturerIDCodeLSB
+                    .to_usize(),
+                location: ddr5::Offset::ModuleManufacturingLocation.to_usize(),
+                year: ddr5::Offset::ModuleManufacturingDateYear.to_usize(),
+                serial: ddr5::Offset::ModuleSerialNumber0.to_usize(),
+                part_number: ddr5::Offset::PartNumberBase.to_usize(),
+                part_number_len: ddr5::Offset::PartNumberLimit.to_usize()
+                    - ddr5::Offset::PartNumberBase.to_usize()
+                    + 1,
+                revision: ddr5::Offset::ModuleRevisionCode.to_usize(),
+                dram_manufacturer: ddr5::Offset::DRAMManufacturerIDCodeLSB
+                    .to_usize(),
+                dram_stepping: ddr5::Offset::DRAMStepping.to_usize(),
+            },
+        }
+    }
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+pub(crate) fn trim(part_number: &[u8; PART_NUMBER_LEN]) -> &[u8] {
+    let len = part_number
+        .iter()
+        .rposition(|&b| b != b' ' && b != 0)
+        .map_or(0, |p| p + 1);
+
+    &part_number[..len]
+}
+
+///
+/// The manufacturing identity of a module, which can be decoded from any
+/// image whose layout is known (or assumed).
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ModuleIdentity {
+    pub module_manufacturer: u16,
+    pub dram_manufacturer: u16,
+    pub serial: u32,
+    /// Manufacturing year and week, in BCD.
+    pub year: u8,
+    pub week: u8,
+    /// Part number, space padded.
+    pub part_number: [u8; PART_NUMBER_LEN],
+}
+
+impl ModuleIdentity {
+    ///
+    /// Decode the identity fields at their locations in the given device
+    /// type's layout.  Returns `None` if the image is too short.
+    ///
+    pub fn decode(buf: &[u8], layout: DeviceType) -> Option<Self> {
+        let identity = Identity::of(layout);
+
+        if buf.len() <= identity.dram_stepping {
+            return None;
+        }
+
+        let mut part_number = [b' '; PART_NUMBER_LEN];
+        part_number[..identity.part_number_len].copy_from_slice(
+            &buf[identity.part_number
+                ..identity.part_number + identity.part_number_len],
+        );
+
+        let s = identity.serial;
+
+        Some(Self {
+            module_manufacturer: u16_at(buf, identity.manufacturer),
+            dram_manufacturer: u16_at(buf, identity.dram_manufacturer),
+            serial: u32::from_be_bytes([
+                buf[s],
+                buf[s + 1],
+                buf[s + 2],
+                buf[s + 3],
+            ]),
+            year: buf[identity.year],
+            week: buf[identity.year + 1],
+            part_number,
+        })
+    }
+
+    ///
+    /// The part number with trailing padding removed.
+    ///
+    pub fn part_number(&self) -> &[u8] {
+        trim(&self.part_number)
+    }
+}
+
+impl Summary {
+    ///
+    /// Decode a summary from an image.  Returns `None` if the device type is
+    /// not recognized or the image is too short to contain the identity
+    /// fields.
+    ///
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        let device_type = DeviceType::from_spd(buf)?;
+        let identity = ModuleIdentity::decode(buf, device_type)?;
+
+        let (tck_min, taa_min, organization, crc_valid) = match device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => (
+                Timing::TCkAvgMin.within(buf),
+                Timing::TAAMin.within(buf),
+                Organization::from_spd(buf).map(|o| (o.capacity(), o.ranks)),
+                Some(verify_crc(buf)),
+            ),
+            DeviceType::Ddr5 => (
+                ddr5::Timing::TCkAvgMin.within(buf),
+                ddr5::Timing::TAAMin.within(buf),
+                ddr5::Organization::from_spd(buf)
+                    .map(|o| (o.capacity(), o.ranks)),
+                Some(ddr5::verify_crc(buf)),
+            ),
+        };
+
+        Some(Self {
+            device_type,
+            module_type: Offset::ModuleType.within(buf),
+            module_manufacturer: identity.module_manufacturer,
+            dram_manufacturer: identity.dram_manufacturer,
+            serial: identity.serial,
+            year: identity.year,
+            week: identity.week,
+            part_number: identity.part_number,
+            tck_min,
+            taa_min,
+            capacity: organization.map(|(capacity, _)| capacity),
+            ranks: organization.map(|(_, ranks)| ranks),
+            crc_valid,
+        })
+    }
+
+    ///
+    /// The part number with trailing padding removed.
+    ///
+    pub fn part_number(&self) -> &[u8] {
+        trim(&self.part_number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{update_crc, MAX_SIZE};
+
+    #[test]
+    fn ddr4() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x003] = 0x01;
+        buf[0x012] = 0x05;
+        buf[0x018] = 0x6e;
+        buf[0x140] = 0x80;
+        buf[0x141] = 0xce;
+        buf[0x143] = 0x21;
+        buf[0x144] = 0x07;
+        buf[0x145..0x149].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        buf[0x149..0x15d].copy_from_slice(b"M393A4K40DB3-CWE    ");
+        update_crc(&mut buf);
+
+        let summary = Summary::from_spd(&buf).unwrap();
+        assert_eq!(summary.device_type, DeviceType::Ddr4);
+        assert_eq!(summary.module_type, 0x01);
+        assert_eq!(summary.module_manufacturer, 0xce80);
+        assert_eq!(summary.serial, 0xdead_beef);
+        assert_eq!(summary.year, 0x21);
+        assert_eq!(summary.week, 0x07);
+        assert_eq!(summary.part_number(), b"M393A4K40DB3-CWE");
+        assert_eq!(summary.tck_min, Picoseconds(625));
+        assert_eq!(summary.taa_min, Picoseconds(13750));
+        assert_eq!(summary.crc_valid, Some(true));
+        assert_eq!(summary.capacity, Some(64 << 20));
+        assert_eq!(summary.ranks, Some(1));
+
+        assert_eq!(Summary::from_spd(&buf[..256]), None);
+        assert_eq!(Summary::from_spd(&[0; 2]), None);
+    }
+
+    #[test]
+    fn ddr5() {
+        let mut buf = [0u8; 1024];
+        buf[0x002] = 0x12;
+        buf[0x004] = 0x04;
+        buf[0x006] = 0x20;
+        buf[0x014] = 0xa0;
+        buf[0x015] = 0x01;
+        buf[0x0ea] = 0x08;
+        buf[0x0eb] = 0x32;
+        buf[0x209..0x227].copy_from_slice(b"M321R4GA3BB6-CQK              ");
+
+        let summary = Summary::from_spd(&buf).unwrap();
+        assert_eq!(summary.device_type, DeviceType::Ddr5);
+        assert_eq!(summary.tck_min, Picoseconds(416));
+        assert_eq!(summary.capacity, Some(32 << 30));
+        assert_eq!(summary.crc_valid, Some(false));
+
+        crate::ddr5::update_crc(&mut buf);
+        assert_eq!(Summary::from_spd(&buf).unwrap().crc_valid, Some(true));
+        assert_eq!(summary.part_number(), b"M321R4GA3BB6-CQK");
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(Summary::from_spd(&[0u8; MAX_SIZE]), None);
+    }
+}
@@ -0,0 +1,245 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::tse2004::{Limits, Temperature};
+use crate::{ddr5, DeviceType, Offset};
+
+///
+/// The top of the normal operating temperature range, above which DRAM
+/// must be refreshed twice as often.
+///
+pub const DOUBLE_REFRESH_THRESHOLD: Temperature = Temperature::from_celsius(85);
+
+///
+/// The top of the extended operating temperature range.
+///
+pub const EXTENDED_TEMPERATURE_LIMIT: Temperature =
+    Temperature::from_celsius(95);
+
+///
+/// Bit of the DDR4 module thermal sensor byte indicating that a sensor is
+/// incorporated; for DDR5, the same bit of the hub device type indicates
+/// that the hub is installed.
+///
+const SENSOR_INSTALLED: u8 = 1 << 7;
+
+///
+/// The DDR5 hub device type (bits 3:0 of byte 196) of an SPD5118, which
+/// has an integrated temperature sensor.  Other hubs, such as the SPD5108,
+/// are not assumed to have one.
+///
+const HUB_SPD5118: u8 = 0b0000;
+
+///
+/// Bits of the DDR5 temperature sensor device type (byte 212) indicating
+/// that discrete sensors TS1 and TS0 are installed.
+///
+const TS_INSTALLED: u8 = 0b11 << 6;
+
+///
+/// Returns true if a DDR5 module has a temperature sensor, whether in its
+/// hub or discrete.
+///
+fn ddr5_sensor(buf: &[u8]) -> Option<bool> {
+    let hub = *buf.get(ddr5::Offset::SPDHubDeviceType.to_usize())?;
+    let ts = *buf.get(ddr5::Offset::TemperatureSensorDeviceType.to_usize())?;
+
+    Some(
+        (hub & SENSOR_INSTALLED != 0 && hub & 0b1111 == HUB_SPD5118)
+            || ts & TS_INSTALLED != 0,
+    )
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RefreshRate {
+    /// 1x refresh, within the normal temperature range.
+    Normal,
+    /// 2x refresh, within the extended temperature range.
+    Double,
+}
+
+///
+/// What the memory controller should do at a given temperature.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Recommendation {
+    pub refresh: RefreshRate,
+    /// The average refresh interval (tREFI) for the refresh rate, in
+    /// nanoseconds.
+    pub trefi_ns: u32,
+    /// The module is above its critical limit:  traffic should be
+    /// throttled until it cools.
+    pub throttle: bool,
+}
+
+///
+/// The thermal policy for a module:  2x refresh above 85 °C, as JEDEC
+/// requires, and throttling above a critical limit.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ThermalPolicy {
+    pub device_type: DeviceType,
+    /// The module has a temperature sensor.
+    pub sensor: bool,
+    /// The temperature below which an alarm is raised (and, on DDR5, the
+    /// critical low limit).
+    pub low: Temperature,
+    /// The temperature above which traffic must be throttled.
+    pub critical: Temperature,
+}
+
+impl ThermalPolicy {
+    ///
+    /// The default policy for the module described by an image, or `None`
+    /// if its type is unknown or it is too short.
+    ///
+    pub fn from_spd(buf: &[u8]) -> Option<Self> {
+        let device_type = DeviceType::from_spd(buf)?;
+        let sensor = match device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => {
+                buf.get(Offset::ModuleThermalSensor.to_usize())?
+                    & SENSOR_INSTALLED
+                    != 0
+            }
+            DeviceType::Ddr5 => ddr5_sensor(buf)?,
+        };
+
+        Some(Self {
+            device_type,
+            sensor,
+            low: Temperature::from_celsius(0),
+            critical: EXTENDED_TEMPERATURE_LIMIT,
+        })
+    }
+
+    ///
+    /// The recommendation at a given temperature.  If the temperature is not
+    /// known (for example, because the module has no sensor), 2x refresh is
+    /// assumed but throttling is not.
+    ///
+    pub fn recommend(
+        &self,
+        temperature: Option<Temperature>,
+    ) -> Recommendation {
+        let refresh = match temperature {
+            Some(t) if t <= DOUBLE_REFRESH_THRESHOLD => RefreshRate::Normal,
+            _ => RefreshRate::Double,
+        };
+
+        //
+        // tREFI is 7.8 µs for DDR4 and 3.9 µs for DDR5 in the normal range,
+        // and is halved in the extended range.
+        //
+        let trefi_ns = match self.device_type {
+            DeviceType::Ddr4 | DeviceType::Ddr4E => 7800,
+            DeviceType::Ddr5 => 3900,
+        };
+
+        Recommendation {
+            refresh,
+            trefi_ns: match refresh {
+                RefreshRate::Normal => trefi_ns,
+                RefreshRate::Double => trefi_ns / 2,
+            },
+            throttle: temperature.is_some_and(|t| t > self.critical),
+        }
+    }
+
+    ///
+    /// The TSE2004 limits that raise an event when 2x refresh becomes
+    /// necessary, and when the critical limit is exceeded.
+    ///
+    pub fn tse2004_limits(&self) -> Limits {
+        Limits {
+            high: DOUBLE_REFRESH_THRESHOLD,
+            low: self.low,
+            critical: self.critical,
+        }
+    }
+
+    ///
+    /// The equivalent limits for the sensor of a DDR5 hub.
+    ///
+    pub fn hub_limits(&self) -> ddr5::ThermalLimits {
+        ddr5::ThermalLimits {
+            high: DOUBLE_REFRESH_THRESHOLD,
+            low: self.low,
+            critical_high: self.critical,
+            critical_low: self.low,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DDR5_MAX_SIZE, MAX_SIZE};
+
+    #[test]
+    fn ddr4() {
+        let mut buf = [0u8; MAX_SIZE];
+        buf[0x002] = 0x0c;
+        buf[0x00e] = 0x80;
+
+        let policy = ThermalPolicy::from_spd(&buf).unwrap();
+        assert!(policy.sensor);
+
+        let at = |c| policy.recommend(Some(Temperature::from_celsius(c)));
+        assert_eq!(at(85).refresh, RefreshRate::Normal);
+        assert_eq!(at(85).trefi_ns, 7800);
+        assert_eq!(
+            at(86),
+            Recommendation {
+                refresh: RefreshRate::Double,
+                trefi_ns: 3900,
+                throttle: false,
+            }
+        );
+        assert!(at(96).throttle);
+
+        let none = policy.recommend(None);
+        assert_eq!(none.refresh, RefreshRate::Double);
+        assert!(!none.throttle);
+
+        assert_eq!(
+            policy.tse2004_limits().writes(),
+            Some([[0x02, 0x05, 0x50], [0x03, 0x00, 0x00], [0x04, 0x05, 0xf0]])
+        );
+
+        assert_eq!(ThermalPolicy::from_spd(&buf[..4]), None);
+        assert_eq!(ThermalPolicy::from_spd(&[0; 2]), None);
+    }
+
+    #[test]
+    fn ddr5() {
+        let mut buf = [0u8; DDR5_MAX_SIZE];
+        buf[0x002] = 0x12;
+
+        let policy = ThermalPolicy::from_spd(&buf).unwrap();
+        assert!(!policy.sensor);
+
+        //
+        // A hub other than an SPD5118 has no sensor of its own, but the
+        // module may have discrete sensors.
+        //
+        buf[0x0c4] = 0x81;
+        assert!(!ThermalPolicy::from_spd(&buf).unwrap().sensor);
+        buf[0x0d4] = 0x40;
+        assert!(ThermalPolicy::from_spd(&buf).unwrap().sensor);
+
+        buf[0x0c4] = 0x80;
+        buf[0x0d4] = 0x00;
+        let policy = ThermalPolicy::from_spd(&buf).unwrap();
+        assert!(policy.sensor);
+
+        let at = |c| policy.recommend(Some(Temperature::from_celsius(c)));
+        assert_eq!(at(25).trefi_ns, 3900);
+        assert_eq!(at(90).trefi_ns, 1950);
+
+        let writes = policy.hub_limits().writes().unwrap();
+        assert_eq!(writes[4..6], [[32, 0xf0], [33, 0x05]]);
+    }
+}
@@ -0,0 +1,271 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::Offset;
+
+///
+/// The medium timebase, in picoseconds.  DDR4 defines only one timebase
+/// (byte 17 must be 0), so this is fixed.
+///
+pub const MTB_PS: u32 = 125;
+
+///
+/// The fine timebase, in picoseconds.
+///
+pub const FTB_PS: u32 = 1;
+
+///
+/// A duration in picoseconds.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Picoseconds(pub u32);
+
+///
+/// Decode a timing parameter from its medium timebase count and its (signed)
+/// fine timebase correction.
+///
+pub fn decode_timing(mtb: u8, ftb: i8) -> Picoseconds {
+    let ps = mtb as i32 * MTB_PS as i32 + ftb as i32 * FTB_PS as i32;
+    Picoseconds(ps.max(0) as u32)
+}
+
+///
+/// Encode a timing parameter as a medium timebase count and a fine timebase
+/// correction.  Per JEDEC, the medium timebase count is rounded up and the
+/// fine correction is zero or negative; only values beyond the reach of the
+/// largest medium timebase count use a positive correction.  Returns `None`
+/// if the value cannot be represented.
+///
+pub fn encode_timing(ps: Picoseconds) -> Option<(u8, i8)> {
+    let mtb = ps.0.div_ceil(MTB_PS);
+
+    if mtb <= u8::MAX as u32 {
+        let ftb = (ps.0 as i32 - (mtb * MTB_PS) as i32) / FTB_PS as i32;
+        Some((mtb as u8, ftb as i8))
+    } else {
+        let ftb = (ps.0 - u8::MAX as u32 * MTB_PS) / FTB_PS;
+
+        if ftb <= i8::MAX as u32 {
+            Some((u8::MAX, ftb as i8))
+        } else {
+            None
+        }
+    }
+}
+
+///
+/// The correction applied when converting a timing to clock cycles, in
+/// thousandths of a clock:  a result less than 0.026 clocks above a whole
+/// number rounds down rather than up, absorbing the error of tCK values
+/// that have been truncated to whole picoseconds.
+///
+const ROUNDING_CORRECTION: u64 = 974;
+
+///
+/// Convert a timing to a number of clock cycles at the given tCK, using the
+/// integer rounding algorithm of JEDEC 21-C Annex L, so that results agree
+/// exactly with other implementations.  Saturates at `u16::MAX` (including
+/// for a zero tCK).
+///
+pub fn clocks(timing: Picoseconds, tck: Picoseconds) -> u16 {
+    if tck.0 == 0 {
+        return u16::MAX;
+    }
+
+    let nck =
+        (timing.0 as u64 * 1000 / tck.0 as u64 + ROUNDING_CORRECTION) / 1000;
+
+    nck.min(u16::MAX as u64) as u16
+}
+
+///
+/// DDR4 timing parameters encoded as a medium timebase byte and a fine
+/// timebase correction byte.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Timing {
+    TCkAvgMin,
+    TCkAvgMax,
+    TAAMin,
+    TRCDMin,
+    TRPMin,
+    TRRDSMin,
+    TRRDLMin,
+    TCCDLMin,
+}
+
+impl Timing {
+    pub const ALL: &'static [Timing] = &[
+        Timing::TCkAvgMin,
+        Timing::TCkAvgMax,
+        Timing::TAAMin,
+        Timing::TRCDMin,
+        Timing::TRPMin,
+        Timing::TRRDSMin,
+        Timing::TRRDLMin,
+        Timing::TCCDLMin,
+    ];
+
+    ///
+    /// The offsets of the medium and fine timebase bytes.
+    ///
+    pub fn offsets(self) -> (Offset, Offset) {
+        match self {
+            Timing::TCkAvgMin => (Offset::TCkAvgMin, Offset::TCkAvgMinFine),
+            Timing::TCkAvgMax => (Offset::TCkAvgMax, Offset::TCkAvgMaxFine),
+            Timing::TAAMin => (Offset::TAAMin, Offset::TAAMinFine),
+            Timing::TRCDMin => (Offset::TRCDMin, Offset::TRCDMinFine),
+            Timing::TRPMin => (Offset::TRPMin, Offset::TRPMinFine),
+            Timing::TRRDSMin => (Offset::TRRDSMin, Offset::TRRDSMinFine),
+            Timing::TRRDLMin => (Offset::TRRDLMin, Offset::TRRDLMinFine),
+            Timing::TCCDLMin => (Offset::TCCDLMin, Offset::TCCDLMinFine),
+        }
+    }
+
+    pub fn within(self, buf: &[u8]) -> Picoseconds {
+        let (mtb, ftb) = self.offsets();
+        decode_timing(mtb.within(buf), ftb.within(buf) as i8)
+    }
+
+    ///
+    /// Encode the given value into the image.  Returns `None` (leaving the
+    /// image untouched) if the value cannot be represented.
+    ///
+    pub fn set(self, buf: &mut [u8], ps: Picoseconds) -> Option<()> {
+        let (mtb, ftb) = self.offsets();
+        let (m, f) = encode_timing(ps)?;
+
+        buf[mtb.to_usize()] = m;
+        buf[ftb.to_usize()] = f as u8;
+        Some(())
+    }
+}
+
+///
+/// The CAS latencies supported by a DDR4 device.  Bit n of the four-byte
+/// bitmap indicates support for CL 7 + n or, if bit 31 is set, CL 23 + n.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CasLatencies(pub u32);
+
+impl CasLatencies {
+    const HIGH_RANGE: u32 = 1 << 31;
+
+    pub fn from_spd(buf: &[u8]) -> Self {
+        Self(u32::from_le_bytes([
+            Offset::CASLatencies0.within(buf),
+            Offset::CASLatencies1.within(buf),
+            Offset::CASLatencies2.within(buf),
+            Offset::CASLatencies3.within(buf),
+        ]))
+    }
+
+    fn base(&self) -> u8 {
+        if self.0 & Self::HIGH_RANGE != 0 {
+            23
+        } else {
+            7
+        }
+    }
+
+    pub fn supports(&self, cl: u8) -> bool {
+        let base = self.base();
+
+        (base..base + 30).contains(&cl) && self.0 & (1 << (cl - base)) != 0
+    }
+
+    ///
+    /// Iterate over the supported CAS latencies in ascending order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        let base = self.base();
+        (base..base + 30).filter(move |&cl| self.supports(cl))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_SIZE;
+
+    #[test]
+    fn roundtrip() {
+        let max = u8::MAX as u32 * MTB_PS + i8::MAX as u32;
+
+        for ps in 0..=max {
+            let (mtb, ftb) = encode_timing(Picoseconds(ps)).unwrap();
+            assert_eq!(decode_timing(mtb, ftb), Picoseconds(ps));
+
+            if mtb != u8::MAX {
+                assert!(ftb <= 0);
+            }
+        }
+
+        assert_eq!(encode_timing(Picoseconds(max + 1)), None);
+    }
+
+    #[test]
+    fn jedec() {
+        //
+        // DDR4-3200 tCKAVGmin (625 ps) and a tAAmin of 13.75 ns.
+        //
+        assert_eq!(encode_timing(Picoseconds(625)), Some((0x05, 0)));
+        assert_eq!(encode_timing(Picoseconds(13750)), Some((0x6e, 0)));
+
+        //
+        // DDR4-2933 tCKAVGmin (682 ps) is 6 MTB less 68 FTB.
+        //
+        assert_eq!(encode_timing(Picoseconds(682)), Some((0x06, -68)));
+
+        let mut buf = [0u8; MAX_SIZE];
+        Timing::TCkAvgMin.set(&mut buf, Picoseconds(682)).unwrap();
+        assert_eq!(buf[0x12], 0x06);
+        assert_eq!(buf[0x7d], 0xbc);
+        assert_eq!(Timing::TCkAvgMin.within(&buf), Picoseconds(682));
+    }
+
+    #[test]
+    fn clocks() {
+        let ps = Picoseconds;
+
+        assert_eq!(super::clocks(ps(13750), ps(625)), 22);
+        assert_eq!(super::clocks(ps(13751), ps(625)), 22);
+        assert_eq!(super::clocks(ps(13767), ps(625)), 23);
+
+        //
+        // DDR4-2133 (tCK of 938 ps, truncated from 937.5):  13.5 ns is
+        // 14.39 clocks, and 13.13 ns is 13.997.
+        //
+        assert_eq!(super::clocks(ps(13500), ps(938)), 15);
+        assert_eq!(super::clocks(ps(13130), ps(938)), 14);
+        assert_eq!(super::clocks(ps(14040), ps(938)), 15);
+
+        assert_eq!(super::clocks(ps(0), ps(625)), 0);
+        assert_eq!(super::clocks(ps(u32::MAX), ps(1)), u16::MAX);
+        assert_eq!(super::clocks(ps(1), ps(0)), u16::MAX);
+    }
+
+    #[test]
+    fn cas_latencies() {
+        let mut buf = [0u8; MAX_SIZE];
+
+        //
+        // CL 10 through 20 and 22.
+        //
+        buf[0x14] = 0b1111_1000;
+        buf[0x15] = 0b1011_1111;
+
+        let cl = CasLatencies::from_spd(&buf);
+        assert!(cl.supports(10) && cl.supports(22));
+        assert!(!cl.supports(9) && !cl.supports(21));
+        assert_eq!(cl.iter().count(), 12);
+
+        buf[0x17] = 0b1000_0000;
+        let cl = CasLatencies::from_spd(&buf);
+        assert_eq!(cl.iter().next(), Some(26));
+        assert!(!cl.supports(10));
+    }
+}
@@ -0,0 +1,444 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::{DeviceType, Function, SelectAddress};
+
+///
+/// A single DIMM slot as seen on the bus:  its select address, the device
+/// code of its SPD EEPROM and, if present, the device code of its temperature
+/// sensor.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Slot {
+    pub select_address: SelectAddress,
+    pub memory: u8,
+    pub temperature: Option<u8>,
+}
+
+///
+/// The set of devices discovered by a bus scan, organized by select address.
+/// Every DIMM on an EE1004 bus is strapped to one of eight select addresses,
+/// and both its EEPROM and its (optional) temperature sensor respond at that
+/// select address.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Topology {
+    memory: u8,
+    temperature: u8,
+}
+
+impl Topology {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Construct a topology from the device codes that responded to a scan.
+    /// Codes that don't correspond to a memory or temperature function are
+    /// ignored.
+    ///
+    pub fn from_device_codes(codes: &[u8]) -> Self {
+        let mut topology = Self::new();
+
+        for &code in codes {
+            topology.add(code);
+        }
+
+        topology
+    }
+
+    ///
+    /// Record a device code as present.  Returns the function of the code,
+    /// if any.
+    ///
+    pub fn add(&mut self, code: u8) -> Option<Function> {
+        let func = Function::from_device_code(code)?;
+
+        match func {
+            Function::Memory(sa) => self.memory |= 1 << sa.get(),
+            Function::Temperature(sa) => self.temperature |= 1 << sa.get(),
+            _ => {}
+        }
+
+        Some(func)
+    }
+
+    ///
+    /// Returns true if an SPD EEPROM responded at the given select address.
+    ///
+    pub fn populated(&self, sa: SelectAddress) -> bool {
+        self.memory & (1 << sa.get()) != 0
+    }
+
+    ///
+    /// Returns true if a temperature sensor responded at the given select
+    /// address.
+    ///
+    pub fn has_temperature(&self, sa: SelectAddress) -> bool {
+        self.temperature & (1 << sa.get()) != 0
+    }
+
+    ///
+    /// Return the slot at the given select address, if it is populated.
+    ///
+    pub fn slot(&self, sa: SelectAddress) -> Option<Slot> {
+        if !self.populated(sa) {
+            return None;
+        }
+
+        Some(Slot {
+            select_address: sa,
+            memory: Function::Memory(sa).to_device_code()?,
+            temperature: if self.has_temperature(sa) {
+                Function::Temperature(sa).to_device_code()
+            } else {
+                None
+            },
+        })
+    }
+
+    ///
+    /// Iterate over all populated slots in select address order.
+    ///
+    pub fn slots(&self) -> impl Iterator<Item = Slot> + '_ {
+        SelectAddress::all().filter_map(move |sa| self.slot(sa))
+    }
+
+    ///
+    /// Select addresses at which a temperature sensor responded without an
+    /// accompanying SPD EEPROM.
+    ///
+    pub fn orphaned_sensors(&self) -> impl Iterator<Item = SelectAddress> + '_ {
+        SelectAddress::all()
+            .filter(move |&sa| self.has_temperature(sa) && !self.populated(sa))
+    }
+
+    pub fn count(&self) -> u32 {
+        self.memory.count_ones()
+    }
+}
+
+///
+/// A device on a DDR5 module that sits on the bus alongside the SPD5118 hub,
+/// at a device code formed from its own device type identifier and the
+/// module's select address.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LocalDevice {
+    /// Temperature sensor 0.
+    Ts0,
+    /// Temperature sensor 1, whose device codes coincide with the EE1004
+    /// page and protection functions.
+    Ts1,
+    /// Power management IC.
+    Pmic,
+    /// Registering clock driver.
+    Rcd,
+}
+
+impl LocalDevice {
+    const ALL: [LocalDevice; 4] = [
+        LocalDevice::Ts0,
+        LocalDevice::Ts1,
+        LocalDevice::Pmic,
+        LocalDevice::Rcd,
+    ];
+
+    fn dtid(self) -> u8 {
+        match self {
+            LocalDevice::Ts0 => 0b0010,
+            LocalDevice::Ts1 => 0b0110,
+            LocalDevice::Pmic => 0b1001,
+            LocalDevice::Rcd => 0b1011,
+        }
+    }
+
+    pub fn from_device_code(code: u8) -> Option<(Self, SelectAddress)> {
+        let device = Self::ALL.iter().find(|d| d.dtid() == code >> 3)?;
+        Some((*device, SelectAddress::try_new(code & 0b111)?))
+    }
+
+    pub fn to_device_code(self, sa: SelectAddress) -> u8 {
+        self.dtid() << 3 | sa.get()
+    }
+
+    fn bit(self, sa: SelectAddress) -> u32 {
+        1 << (self as u32 * 8 + sa.get() as u32)
+    }
+}
+
+///
+/// What a responding device code is, in the context of the rest of the scan.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DeviceClass {
+    /// An EE1004 SPD EEPROM.
+    Memory(SelectAddress),
+    /// An SPD5118 hub, which responds at the memory device code but not to
+    /// the EE1004 page and protection functions.
+    Hub(SelectAddress),
+    /// A TSE2004 temperature sensor.
+    Temperature(SelectAddress),
+    /// A device local to the DDR5 module at the given select address.
+    Local(LocalDevice, SelectAddress),
+    /// An EE1004 page or protection function, shared by all EEPROMs.
+    Broadcast(Function),
+    /// A device that is not part of the SPD address space.
+    Unrecognized,
+}
+
+///
+/// Something inconsistent in a scan.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Anomaly {
+    /// A temperature sensor responded without a memory device at the same
+    /// select address.
+    OrphanedSensor(SelectAddress),
+    /// A temperature sensor responded on a DDR5 bus, where sensors sit
+    /// behind the hub and are not directly addressable.
+    UnexpectedSensor(SelectAddress),
+    /// The EE1004 page and protection functions responded, but no memory
+    /// device did.
+    BroadcastWithoutMemory,
+    /// A device outside of the SPD address space responded.
+    UnrecognizedDevice(u8),
+}
+
+///
+/// The population of a bus, inferred from the 7-bit addresses that
+/// responded to a raw scan:  each is classified, grouped into slots, and
+/// checked for consistency.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PopulationMap {
+    topology: Topology,
+    broadcast: bool,
+    local: u32,
+    unrecognized: u128,
+}
+
+impl PopulationMap {
+    pub fn infer(codes: &[u8]) -> Self {
+        let mut map = Self::default();
+
+        for &code in codes {
+            if let Some((device, sa)) = LocalDevice::from_device_code(code) {
+                map.local |= device.bit(sa);
+            }
+
+            match map.topology.add(code) {
+                Some(Function::Memory(_)) | Some(Function::Temperature(_)) => {}
+                Some(_) => map.broadcast = true,
+                None if code < 0x80 => map.unrecognized |= 1 << code,
+                None => {}
+            }
+        }
+
+        //
+        // The codes of the broadcast functions are also those of a DDR5
+        // module's second temperature sensor.  If any of a module's other
+        // local devices answered alongside its hub, the bus is DDR5 and
+        // those codes were sensors.
+        //
+        let ddr5 = map.slots().any(|slot| {
+            [LocalDevice::Ts0, LocalDevice::Pmic, LocalDevice::Rcd]
+                .iter()
+                .any(|&d| map.has_local(d, slot.select_address))
+        });
+
+        if ddr5 {
+            map.broadcast = false;
+        }
+
+        map
+    }
+
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+
+    fn has_local(&self, device: LocalDevice, sa: SelectAddress) -> bool {
+        self.local & device.bit(sa) != 0
+    }
+
+    ///
+    /// The memory generation on the bus, if any memory device responded:
+    /// EE1004 EEPROMs answer the page and protection functions, while
+    /// SPD5118 hubs do not.
+    ///
+    pub fn generation(&self) -> Option<DeviceType> {
+        if self.topology.count() == 0 {
+            None
+        } else if self.broadcast {
+            Some(DeviceType::Ddr4)
+        } else {
+            Some(DeviceType::Ddr5)
+        }
+    }
+
+    ///
+    /// Classify a device code that responded.  Returns `None` if it did not
+    /// respond to the scan.
+    ///
+    pub fn classify(&self, code: u8) -> Option<DeviceClass> {
+        let ddr5 = self.generation() == Some(DeviceType::Ddr5);
+
+        if let Some((device, sa)) = LocalDevice::from_device_code(code) {
+            if ddr5 && self.has_local(device, sa) {
+                return Some(DeviceClass::Local(device, sa));
+            }
+        }
+
+        match Function::from_device_code(code) {
+            Some(Function::Memory(sa)) if self.topology.populated(sa) => {
+                Some(match self.generation() {
+                    Some(DeviceType::Ddr5) => DeviceClass::Hub(sa),
+                    _ => DeviceClass::Memory(sa),
+                })
+            }
+            Some(Function::Temperature(sa))
+                if self.topology.has_temperature(sa) =>
+            {
+                Some(DeviceClass::Temperature(sa))
+            }
+            Some(Function::Memory(_)) | Some(Function::Temperature(_)) => None,
+            Some(func) if self.broadcast => Some(DeviceClass::Broadcast(func)),
+            Some(_) => None,
+            None if code < 0x80 && self.unrecognized & (1 << code) != 0 => {
+                Some(DeviceClass::Unrecognized)
+            }
+            None => None,
+        }
+    }
+
+    pub fn slots(&self) -> impl Iterator<Item = Slot> + '_ {
+        self.topology.slots()
+    }
+
+    pub fn anomalies(&self) -> impl Iterator<Item = Anomaly> + '_ {
+        let ddr5 = self.generation() == Some(DeviceType::Ddr5);
+
+        let sensors = SelectAddress::all()
+            .filter(move |&sa| self.topology.has_temperature(sa))
+            .filter_map(move |sa| {
+                if !self.topology.populated(sa) {
+                    Some(Anomaly::OrphanedSensor(sa))
+                } else if ddr5 {
+                    Some(Anomaly::UnexpectedSensor(sa))
+                } else {
+                    None
+                }
+            });
+
+        let broadcast = if self.broadcast && self.topology.count() == 0 {
+            Some(Anomaly::BroadcastWithoutMemory)
+        } else {
+            None
+        };
+
+        let unrecognized = (0..0x80u8)
+            .filter(move |&code| {
+                self.classify(code) == Some(DeviceClass::Unrecognized)
+            })
+            .map(Anomaly::UnrecognizedDevice);
+
+        sensors.chain(broadcast).chain(unrecognized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topology() {
+        let sa = SelectAddress::new;
+        let t = Topology::from_device_codes(&[0x18, 0x50, 0x52, 0x36, 0x1b]);
+
+        assert_eq!(t.count(), 2);
+        assert!(t.populated(sa(0)));
+        assert!(!t.populated(sa(1)));
+        assert!(t.populated(sa(2)));
+        assert!(t.has_temperature(sa(0)));
+        assert!(!t.has_temperature(sa(2)));
+
+        assert_eq!(
+            t.slot(sa(0)),
+            Some(Slot {
+                select_address: sa(0),
+                memory: 0x50,
+                temperature: Some(0x18)
+            })
+        );
+        assert_eq!(t.slot(sa(2)).unwrap().temperature, None);
+        assert_eq!(t.slots().count(), 2);
+        assert_eq!(t.orphaned_sensors().next(), Some(sa(3)));
+    }
+
+    #[test]
+    fn population() {
+        let sa = SelectAddress::new;
+
+        let map = PopulationMap::infer(&[0x18, 0x19, 0x50, 0x36, 0x37, 0x68]);
+        assert_eq!(map.generation(), Some(DeviceType::Ddr4));
+        assert_eq!(map.classify(0x50), Some(DeviceClass::Memory(sa(0))));
+        assert_eq!(map.classify(0x18), Some(DeviceClass::Temperature(sa(0))));
+        assert_eq!(
+            map.classify(0x37),
+            Some(DeviceClass::Broadcast(Function::PageAddress(crate::Page(
+                1
+            ))))
+        );
+        assert_eq!(map.classify(0x68), Some(DeviceClass::Unrecognized));
+        assert_eq!(map.classify(0x51), None);
+        assert_eq!(map.slots().count(), 1);
+
+        let mut anomalies = map.anomalies();
+        assert_eq!(anomalies.next(), Some(Anomaly::OrphanedSensor(sa(1))));
+        assert_eq!(anomalies.next(), Some(Anomaly::UnrecognizedDevice(0x68)));
+        assert_eq!(anomalies.next(), None);
+
+        let map = PopulationMap::infer(&[0x50, 0x52, 0x1a]);
+        assert_eq!(map.generation(), Some(DeviceType::Ddr5));
+        assert_eq!(map.classify(0x52), Some(DeviceClass::Hub(sa(2))));
+        assert_eq!(
+            map.anomalies().next(),
+            Some(Anomaly::UnexpectedSensor(sa(2)))
+        );
+
+        //
+        // A DDR5 RDIMM with both temperature sensors, a PMIC and an RCD:
+        // the second sensor answers where an EE1004 broadcast function
+        // would, but the bus is nonetheless DDR5.
+        //
+        let map = PopulationMap::infer(&[0x10, 0x30, 0x48, 0x50, 0x58]);
+        assert_eq!(map.generation(), Some(DeviceType::Ddr5));
+        assert_eq!(map.classify(0x50), Some(DeviceClass::Hub(sa(0))));
+
+        for &device in LocalDevice::ALL.iter() {
+            let code = device.to_device_code(sa(0));
+            assert_eq!(
+                LocalDevice::from_device_code(code),
+                Some((device, sa(0)))
+            );
+            assert_eq!(
+                map.classify(code),
+                Some(DeviceClass::Local(device, sa(0)))
+            );
+        }
+
+        assert_eq!(map.classify(0x31), None);
+        assert_eq!(map.anomalies().next(), None);
+
+        let map = PopulationMap::infer(&[0x36]);
+        assert_eq!(map.generation(), None);
+        assert_eq!(
+            map.anomalies().next(),
+            Some(Anomaly::BroadcastWithoutMemory)
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+//!
+//! Configuration and status of the TSE2004 temperature sensor found on
+//! DDR4 modules, as described in JC-42.4.
+//!
+
+use crate::FromPrimitive;
+
+///
+/// The registers of the temperature sensor.  All registers are 16 bits wide
+/// and transferred most significant byte first.
+///
+#[derive(Copy, Clone, Debug, PartialEq, FromPrimitive)]
+pub enum Register {
+    Capability = 0x00,
+    Configuration = 0x01,
+    HighLimit = 0x02,
+    LowLimit = 0x03,
+    CriticalLimit = 0x04,
+    Temperature = 0x05,
+    ManufacturerID = 0x06,
+    DeviceID = 0x07,
+}
+
+impl Register {
+    ///
+    /// The bytes of an SMBus write of `value` to this register:  the
+    /// register pointer followed by the value, most significant byte first.
+    ///
+    pub fn write(self, value: u16) -> [u8; 3] {
+        let [msb, lsb] = value.to_be_bytes();
+        [self as u8, msb, lsb]
+    }
+}
+
+///
+/// The accuracy class of the sensor, in bit 1 of the capability register.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AccuracyClass {
+    /// ±1 °C over the active range (75 °C to 95 °C).
+    B,
+    /// ±2 °C over the active range.
+    C,
+}
+
+///
+/// The resolution of the temperature register, in bits 4:3 of the
+/// capability register.
+///
+#[derive(Copy, Clone, Debug, PartialEq, FromPrimitive)]
+pub enum Resolution {
+    HalfDegree = 0b00,
+    QuarterDegree = 0b01,
+    EighthDegree = 0b10,
+    SixteenthDegree = 0b11,
+}
+
+impl Resolution {
+    ///
+    /// The resolution, in sixteenths of a degree.
+    ///
+    pub fn sixteenths(self) -> i16 {
+        8 >> (self as u8)
+    }
+}
+
+///
+/// The contents of the (read-only) capability register.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Capability {
+    /// The sensor supports the EVENT_n output and its limits.
+    pub event: bool,
+    pub accuracy: AccuracyClass,
+    /// Temperatures below 0 °C can be read, rather than being clamped.
+    pub negative: bool,
+    pub resolution: Resolution,
+    /// The SA0 pin tolerates high voltage, for writing protection.
+    pub high_voltage: bool,
+    /// The SMBus timeout is supported.
+    pub timeout: bool,
+    /// EVENT_n is deasserted when the sensor is shut down.
+    pub event_shutdown: bool,
+}
+
+impl Capability {
+    pub fn decode(raw: u16) -> Self {
+        let bit = |n: u16| raw & (1 << n) != 0;
+
+        Self {
+            event: bit(0),
+            accuracy: if bit(1) {
+                AccuracyClass::B
+            } else {
+                AccuracyClass::C
+            },
+            negative: bit(2),
+            resolution: Resolution::from_u16((raw >> 3) & 0b11).unwrap(),
+            high_voltage: bit(5),
+            timeout: bit(6),
+            event_shutdown: bit(7),
+        }
+    }
+}
+
+///
+/// A temperature, in sixteenths of a degree Celsius.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Temperature(pub i16);
+
+impl Temperature {
+    ///
+    /// The resolution of the limit registers, in sixteenths of a degree.
+    ///
+    const LIMIT_RESOLUTION: i16 = 4;
+
+    pub const fn from_celsius(degrees: i16) -> Self {
+        Self(degrees * 16)
+    }
+
+    ///
+    /// The temperature in whole degrees Celsius, rounded towards negative
+    /// infinity.
+    ///
+    pub fn celsius(self) -> i16 {
+        self.0 >> 4
+    }
+
+    ///
+    /// Decode the 13-bit two's complement temperature in the low bits of a
+    /// temperature or limit register, ignoring any flags above it.
+    ///
+    pub fn decode(raw: u16) -> Self {
+        Self(((raw << 3) as i16) >> 3)
+    }
+
+    ///
+    /// Decode a temperature register from a sensor of the given resolution,
+    /// discarding the bits below it, which are undefined.
+    ///
+    pub fn decode_with(raw: u16, resolution: Resolution) -> Self {
+        let Self(t) = Self::decode(raw);
+        Self(t & !(resolution.sixteenths() - 1))
+    }
+
+    ///
+    /// Encode as the value of a limit register, which has a resolution of a
+    /// quarter of a degree.  Returns `None` if the temperature cannot be
+    /// represented exactly.
+    ///
+    pub fn encode_limit(self) -> Option<u16> {
+        if self.0 % Self::LIMIT_RESOLUTION != 0
+            || !(-256 * 16..256 * 16).contains(&self.0)
+        {
+            return None;
+        }
+
+        Some(self.0 as u16 & 0x1fff)
+    }
+}
+
+///
+/// A decoded read of the temperature register:  the ambient temperature
+/// along with the event status flags comparing it to the limits.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Reading {
+    pub temperature: Temperature,
+    pub above_critical: bool,
+    pub above_high: bool,
+    pub below_low: bool,
+}
+
+impl Reading {
+    pub fn decode(raw: u16) -> Self {
+        Self::decode_with(raw, Resolution::SixteenthDegree)
+    }
+
+    ///
+    /// Decode a reading from a sensor of the given resolution, as advertised
+    /// in its capability register.
+    ///
+    pub fn decode_with(raw: u16, resolution: Resolution) -> Self {
+        Self {
+            temperature: Temperature::decode_with(raw, resolution),
+            above_critical: raw & (1 << 15) != 0,
+            above_high: raw & (1 << 14) != 0,
+            below_low: raw & (1 << 13) != 0,
+        }
+    }
+
+    ///
+    /// Returns true if the temperature is outside of the alarm window.
+    ///
+    pub fn alarm(&self) -> bool {
+        self.above_high || self.below_low
+    }
+}
+
+///
+/// The hysteresis applied to the limits, in bits 10:9 of the configuration
+/// register.
+///
+#[derive(Copy, Clone, Debug, PartialEq, FromPrimitive)]
+pub enum Hysteresis {
+    Disabled = 0b00,
+    OneAndAHalf = 0b01,
+    Three = 0b10,
+    Six = 0b11,
+}
+
+///
+/// Whether the EVENT_n output is asserted until the temperature returns
+/// within limits (comparator mode) or until cleared by software
+/// (interrupt mode).
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EventMode {
+    Comparator,
+    Interrupt,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EventPolarity {
+    ActiveLow,
+    ActiveHigh,
+}
+
+///
+/// The contents of the configuration register.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Configuration {
+    pub hysteresis: Hysteresis,
+    /// The sensor is shut down; no conversions are performed.
+    pub shutdown: bool,
+    /// The critical limit cannot be altered.
+    pub critical_lock: bool,
+    /// The high and low limits cannot be altered.
+    pub window_lock: bool,
+    /// Write-only:  clear a pending event in interrupt mode.
+    pub clear_event: bool,
+    /// Read-only:  the EVENT_n output is asserted.
+    pub event_asserted: bool,
+    /// The EVENT_n output is enabled.
+    pub event_enabled: bool,
+    /// Only assert EVENT_n when the critical limit is exceeded.
+    pub critical_only: bool,
+    pub polarity: EventPolarity,
+    pub mode: EventMode,
+}
+
+impl Configuration {
+    pub fn decode(raw: u16) -> Self {
+        let bit = |n: u16| raw & (1 << n) != 0;
+
+        Self {
+            hysteresis: Hysteresis::from_u16((raw >> 9) & 0b11).unwrap(),
+            shutdown: bit(8),
+            critical_lock: bit(7),
+            window_lock: bit(6),
+            clear_event: bit(5),
+            event_asserted: bit(4),
+            event_enabled: bit(3),
+            critical_only: bit(2),
+            polarity: if bit(1) {
+                EventPolarity::ActiveHigh
+            } else {
+                EventPolarity::ActiveLow
+            },
+            mode: if bit(0) {
+                EventMode::Interrupt
+            } else {
+                EventMode::Comparator
+            },
+        }
+    }
+
+    ///
+    /// Encode as a value to be written; the read-only event status is
+    /// always written as zero.
+    ///
+    pub fn encode(&self) -> u16 {
+        let bit = |set: bool, n: u16| if set { 1 << n } else { 0 };
+
+        ((self.hysteresis as u16) << 9)
+            | bit(self.shutdown, 8)
+            | bit(self.critical_lock, 7)
+            | bit(self.window_lock, 6)
+            | bit(self.clear_event, 5)
+            | bit(self.event_enabled, 3)
+            | bit(self.critical_only, 2)
+            | bit(self.polarity == EventPolarity::ActiveHigh, 1)
+            | bit(self.mode == EventMode::Interrupt, 0)
+    }
+}
+
+impl Default for Configuration {
+    ///
+    /// The power-on configuration:  all fields clear.
+    ///
+    fn default() -> Self {
+        Self::decode(0)
+    }
+}
+
+///
+/// The high, low and critical limits.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Limits {
+    pub high: Temperature,
+    pub low: Temperature,
+    pub critical: Temperature,
+}
+
+impl Limits {
+    ///
+    /// The SMBus writes needed to program the limits.  Returns `None` if any
+    /// limit cannot be represented.
+    ///
+    pub fn writes(&self) -> Option<[[u8; 3]; 3]> {
+        Some([
+            Register::HighLimit.write(self.high.encode_limit()?),
+            Register::LowLimit.write(self.low.encode_limit()?),
+            Register::CriticalLimit.write(self.critical.encode_limit()?),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temperature() {
+        assert_eq!(Temperature::decode(0x0190), Temperature::from_celsius(25));
+        assert_eq!(Temperature::decode(0x1ff0).celsius(), -1);
+        assert_eq!(Temperature::decode(0x1e70), Temperature::from_celsius(-25));
+
+        assert_eq!(Temperature::from_celsius(85).encode_limit(), Some(0x0550));
+        assert_eq!(Temperature::from_celsius(-20).encode_limit(), Some(0x1ec0));
+        assert_eq!(Temperature(1).encode_limit(), None);
+        assert_eq!(Temperature::from_celsius(256).encode_limit(), None);
+
+        let reading = Reading::decode(0xc550);
+        assert_eq!(reading.temperature, Temperature::from_celsius(85));
+        assert!(reading.above_critical && reading.above_high);
+        assert!(!reading.below_low);
+        assert!(reading.alarm());
+    }
+
+    #[test]
+    fn capability() {
+        let cap = Capability::decode(0x006f);
+        assert!(cap.event && cap.negative && cap.high_voltage && cap.timeout);
+        assert!(!cap.event_shutdown);
+        assert_eq!(cap.accuracy, AccuracyClass::B);
+        assert_eq!(cap.resolution, Resolution::QuarterDegree);
+
+        let cap = Capability::decode(0x0001);
+        assert_eq!(cap.accuracy, AccuracyClass::C);
+        assert_eq!(cap.resolution, Resolution::HalfDegree);
+
+        //
+        // 25.9375 °C, with undefined low bits on a half-degree part.
+        //
+        let reading = Reading::decode_with(0x019f, cap.resolution);
+        assert_eq!(reading.temperature, Temperature(0x0198));
+        assert_eq!(Reading::decode(0x019f).temperature, Temperature(0x019f));
+        assert_eq!(
+            Temperature::decode_with(0x1fff, Resolution::QuarterDegree),
+            Temperature(-4)
+        );
+    }
+
+    #[test]
+    fn configuration() {
+        let config = Configuration {
+            hysteresis: Hysteresis::Three,
+            event_enabled: true,
+            polarity: EventPolarity::ActiveHigh,
+            mode: EventMode::Interrupt,
+            ..Default::default()
+        };
+
+        assert_eq!(config.encode(), 0x040b);
+        assert_eq!(Configuration::decode(0x040b), config);
+        assert!(Configuration::decode(0x0010).event_asserted);
+        assert_eq!(Configuration::decode(0x0010).encode(), 0);
+
+        assert_eq!(
+            Register::Configuration.write(config.encode()),
+            [0x01, 0x04, 0x0b]
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let limits = Limits {
+            high: Temperature::from_celsius(85),
+            low: Temperature::from_celsius(0),
+            critical: Temperature::from_celsius(95),
+        };
+
+        assert_eq!(
+            limits.writes(),
+            Some([[0x02, 0x05, 0x50], [0x03, 0x00, 0x00], [0x04, 0x05, 0xf0]])
+        );
+
+        let limits = Limits {
+            low: Temperature(-2),
+            ..limits
+        };
+        assert_eq!(limits.writes(), None);
+    }
+}
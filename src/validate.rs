@@ -0,0 +1,154 @@
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+//
+
+use crate::crc::crc16_range;
+use crate::{
+    crc_blocks, ddr5, CrcBlock, DeviceType, Offset, DDR5_MAX_SIZE, MAX_SIZE,
+};
+
+const DDR4: u8 = DeviceType::Ddr4 as u8;
+const DDR4E: u8 = DeviceType::Ddr4E as u8;
+const DDR5: u8 = DeviceType::Ddr5 as u8;
+
+///
+/// The CRC-protected blocks of each generation.
+///
+const DDR4_CRCS: &[CrcBlock] = &crc_blocks();
+const DDR5_CRCS: &[CrcBlock] = &[ddr5::crc_block()];
+
+///
+/// Why an image failed validation.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// The image is not the size required by its device type.
+    Length,
+    /// The device type is not recognized.
+    DeviceType,
+    /// A stored CRC does not match the contents.
+    Crc,
+}
+
+impl ValidationError {
+    pub const fn message(self) -> &'static str {
+        match self {
+            ValidationError::Length => "SPD image has the wrong length",
+            ValidationError::DeviceType => "SPD image has unknown device type",
+            ValidationError::Crc => "SPD image has bad CRC",
+        }
+    }
+}
+
+///
+/// Check the length, device type and CRCs of an image.  This is a const fn
+/// so that images baked into firmware can be checked at compile time; see
+/// [`const_validate_spd!`](crate::const_validate_spd).
+///
+pub const fn validate_spd(image: &[u8]) -> Result<(), ValidationError> {
+    if image.len() <= Offset::DRAMDeviceType as usize {
+        return Err(ValidationError::Length);
+    }
+
+    let (size, crcs) = match image[Offset::DRAMDeviceType as usize] {
+        DDR4 | DDR4E => (MAX_SIZE, DDR4_CRCS),
+        DDR5 => (DDR5_MAX_SIZE, DDR5_CRCS),
+        _ => return Err(ValidationError::DeviceType),
+    };
+
+    if image.len() != size {
+        return Err(ValidationError::Length);
+    }
+
+    let mut i = 0;
+
+    while i < crcs.len() {
+        let block = &crcs[i];
+        let (start, end) = (*block.covered.start(), *block.covered.end());
+        let stored =
+            image[block.crc] as u16 | (image[block.crc + 1] as u16) << 8;
+
+        if crc16_range(image, start, end + 1) != stored {
+            return Err(ValidationError::Crc);
+        }
+
+        i += 1;
+    }
+
+    Ok(())
+}
+
+///
+/// Panic if the image is not valid; in a const context, this fails the
+/// build.
+///
+pub const fn assert_valid_spd(image: &[u8]) {
+    if let Err(err) = validate_spd(image) {
+        panic!("{}", err.message());
+    }
+}
+
+///
+/// Validate an SPD image at compile time, evaluating to the image itself.
+/// Typically the argument is `include_bytes!` of an image in the source
+/// tree.  The build fails if the image has the wrong length, an unknown
+/// device type, or a bad CRC:
+///
+/// ```compile_fail
+/// static SPD: &[u8; 512] = spd::const_validate_spd!(&[0u8; 512]);
+/// ```
+///
+#[macro_export]
+macro_rules! const_validate_spd {
+    ($image:expr) => {{
+        const _: () = $crate::assert_valid_spd($image);
+        $image
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::update_crc;
+
+    const IMAGE: [u8; MAX_SIZE] = {
+        let mut image = [0u8; MAX_SIZE];
+        image[0x002] = DDR4;
+        image[0x003] = 0x01;
+
+        let crc = crc16_range(&image, 0, 0x7e);
+        image[0x07e] = crc as u8;
+        image[0x07f] = (crc >> 8) as u8;
+        image
+    };
+
+    static VALIDATED: &[u8; MAX_SIZE] = const_validate_spd!(&IMAGE);
+
+    #[test]
+    fn validate() {
+        assert_eq!(VALIDATED[0x003], 0x01);
+        assert_eq!(validate_spd(&IMAGE), Ok(()));
+
+        let mut image = IMAGE;
+        image[0x003] = 0x02;
+        assert_eq!(validate_spd(&image), Err(ValidationError::Crc));
+        update_crc(&mut image);
+        assert_eq!(validate_spd(&image), Ok(()));
+
+        assert_eq!(validate_spd(&image[..256]), Err(ValidationError::Length));
+        assert_eq!(validate_spd(&[0; 2]), Err(ValidationError::Length));
+        assert_eq!(validate_spd(&[0; 512]), Err(ValidationError::DeviceType));
+    }
+
+    #[test]
+    fn ddr5() {
+        let image = crate::ddr5::Builder::new(0x01).build().unwrap();
+        assert_eq!(validate_spd(&image), Ok(()));
+
+        let mut image = image;
+        image[0x1fd] ^= 1;
+        assert_eq!(validate_spd(&image), Err(ValidationError::Crc));
+    }
+}